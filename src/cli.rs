@@ -0,0 +1,138 @@
+use std::path::PathBuf;
+
+use jiff::civil::Date;
+use jiff::Zoned;
+
+use crate::config::Config;
+use crate::gui::api::handle_request;
+use crate::gui::crypto::unlock_from_terminal;
+use crate::gui::gui_main::App;
+use crate::instance::{self, ControlCommand, Instance, ReleaseOnDrop};
+use crate::gui::serialize::{data_path, export_to, DateRange};
+
+const USAGE: &str = "usage: time_tracker [start | stop | status | show]\n       time_tracker --export [PATH] [--month YYYY-MM] [--from YYYY-MM-DD] [--to YYYY-MM-DD]";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    // export to the given file or directory, or to the remembered export location.
+    // `month` is used by the monthly timesheet formats and defaults to the current month,
+    // `range` limits the json and markdown reports
+    Export { path: Option<PathBuf>, month: Option<Date>, range: DateRange },
+    // forwarded to the running instance, or run on the data file if there is none
+    Control(ControlCommand),
+}
+
+pub fn parse_args(args: &[String]) -> Result<Option<Command>, String> {
+    match args.first().map(String::as_str) {
+        None => Ok(None),
+        Some("--export") => {
+            let mut path = None;
+            let mut month = None;
+            let mut range = DateRange::default();
+            let mut rest = args[1..].iter();
+            while let Some(arg) = rest.next() {
+                if arg == "--month" || arg == "--from" || arg == "--to" {
+                    let value = rest.next().ok_or(format!("{arg} needs a value\n{USAGE}"))?;
+                    match arg.as_str() {
+                        "--month" => month = Some(parse_month(value)?),
+                        "--from" => range.from = Some(parse_date(value)?),
+                        _ => range.to = Some(parse_date(value)?),
+                    }
+                } else if path.is_none() {
+                    path = Some(PathBuf::from(arg));
+                } else {
+                    return Err(format!("unexpected argument: {arg}\n{USAGE}"));
+                }
+            }
+            Ok(Some(Command::Export { path, month, range }))
+        }
+        Some(arg) => match ControlCommand::parse(arg) {
+            Some(command) if args.len() == 1 => Ok(Some(Command::Control(command))),
+            _ => Err(format!("unknown argument: {arg}\n{USAGE}")),
+        },
+    }
+}
+
+fn parse_month(value: &str) -> Result<Date, String> {
+    format!("{value}-01")
+        .parse::<Date>()
+        .map_err(|_| format!("invalid month {value}, expected YYYY-MM"))
+}
+
+fn parse_date(value: &str) -> Result<Date, String> {
+    value
+        .parse::<Date>()
+        .map_err(|_| format!("invalid date {value}, expected YYYY-MM-DD"))
+}
+
+// runs a command without starting the gui and returns the process exit code
+pub fn run(command: Command) -> i32 {
+    let mut config = Config::get_config();
+
+    match command {
+        Command::Export { path, month, range } => {
+            let path = match path {
+                Some(path) if path.is_dir() => path.join(config.get_export_file_name()),
+                Some(path) => path,
+                None => config.get_export_dir().join(config.get_export_file_name()),
+            };
+            let month = month.unwrap_or(Zoned::now().date());
+            // a running instance keeps the file up to date, otherwise no other one may start writing it meanwhile
+            let _release = match instance::acquire() {
+                Instance::Primary => Some(ReleaseOnDrop),
+                Instance::AlreadyRunning => None,
+            };
+            if let Err(error) = unlock_from_terminal(&config, &data_path()) {
+                eprintln!("could not unlock the data file: {error}");
+                return 1;
+            }
+            match export_to(&path, &config, month, range) {
+                Ok(()) => {
+                    config.remember_export_path(&path);
+                    println!("exported to {}", path.display());
+                    0
+                }
+                Err(error) => {
+                    eprintln!("export to {} failed: {error}", path.display());
+                    1
+                }
+            }
+        }
+        Command::Control(command) => run_control(command),
+    }
+}
+
+fn run_control(command: ControlCommand) -> i32 {
+    let Some(request) = command.api_request() else {
+        return 0;
+    };
+
+    match instance::acquire() {
+        Instance::AlreadyRunning => match instance::send(command) {
+            Ok(response) => {
+                println!("{response}");
+                0
+            }
+            Err(error) => {
+                eprintln!("could not reach the running instance: {error}");
+                1
+            }
+        },
+        // nobody else has the data file open, so it's safe to change it directly
+        Instance::Primary => {
+            if let Err(error) = unlock_from_terminal(&Config::get_config(), &data_path()) {
+                instance::release();
+                eprintln!("could not unlock the data file: {error}");
+                return 1;
+            }
+            let (mut app, _) = App::new();
+            let response = handle_request(&mut app, &request);
+            instance::release();
+            println!("{}", serde_json::to_string(&response.body).unwrap());
+            match response.status {
+                200 => 0,
+                _ => 1,
+            }
+        }
+    }
+}
@@ -2,24 +2,78 @@ use std::env;
 use std::fs;
 use std::fs::File;
 use std::io::prelude::*;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 
 use jiff::civil::date;
-use jiff::civil::Date;
+use jiff::civil::{time, Date, Time};
 use jiff::{Span, Zoned};
 
+use crate::gui::appearance::ThemeChoice;
+use crate::i18n::Language;
+
+pub const DEFAULT_EXPORT_FILE_PATTERN: &str = "work_times_export_{date}.csv";
+pub const DEFAULT_IDLE_MINUTES: u32 = 10;
+pub const DEFAULT_CLOCK_IN_REMINDER: Time = time(10, 0, 0, 0);
+pub const DEFAULT_RUNNING_REMINDER: Time = time(20, 0, 0, 0);
+pub const DEFAULT_MAX_DAILY_HOURS: f32 = 10.;
+pub const DEFAULT_BREAK_AFTER_HOURS: f32 = 6.;
+pub const DEFAULT_FOCUS_MINUTES: u32 = 25;
+pub const DEFAULT_FOCUS_BREAK_MINUTES: u32 = 5;
+
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub struct Config {
     pub hours_week: f32,
     pub start_date: Date,
+    // last directory an export was written to, None until the first export
+    pub export_dir: Option<PathBuf>,
+    // file name suggested in the export dialog, `{date}` is replaced by today's date
+    pub export_file_pattern: String,
+    // printed on the monthly timesheet report
+    pub employee_name: String,
+    // port of the local http api on 127.0.0.1, the api is off without it
+    pub api_port: Option<u16>,
+    // shared folder (Syncthing, Nextcloud, ...) holding the change logs of all devices, no sync without it
+    pub sync_dir: Option<PathBuf>,
+    // names this device's change log in the sync folder, defaults to the host name
+    pub device_name: String,
+    // asks for a passphrase at startup and keeps the data file and journal encrypted
+    pub encrypt_data: bool,
+    // minutes without input until the user is asked what to do with the time away, `idle_minutes = 0` turns it off
+    pub idle_minutes: Option<u32>,
+    // ends the running interval when the machine suspends or shuts down
+    pub stop_on_suspend: bool,
+    // ends the running interval when the screen is locked
+    pub stop_on_lock: bool,
+    // starts again on resume, unlock or the next app start if the interval was ended by one of the above
+    pub start_on_resume: bool,
+    // desktop notifications for the reminders below
+    pub notifications: bool,
+    // reminds to clock in if nothing was recorded on a workday by then, `off` turns it off
+    pub clock_in_reminder: Option<Time>,
+    // reminds that the timer is still running at that time, `off` turns it off
+    pub running_reminder: Option<Time>,
+    // the legal maximum of a day, `0` turns the reminder off
+    pub max_daily_hours: Option<f32>,
+    // hours of work after which a 30 minute break is due, `0` turns the reminder off
+    pub break_after_hours: Option<f32>,
+    // length of a focus block and of the short break after it
+    pub focus_minutes: u32,
+    pub focus_break_minutes: u32,
+    // language of the ui and the csv export, `en` or `de`, defaults to the system locale
+    pub language: Language,
+    // a built-in iced theme by name like `Nord`, or `system` for the desktop's light or dark preference
+    pub theme: ThemeChoice,
+    // width and height, and position of the window when it was last closed
+    pub window_size: Option<(f32, f32)>,
+    pub window_position: Option<(f32, f32)>,
+    // the mini mode showing just the timer and the buttons
+    pub compact: bool,
 }
 
 impl Config {
     pub fn get_config() -> Config {
-        let mut path = env::current_exe().unwrap();
-        path.set_file_name(".config");
-        path.set_extension("txt");
+        let path = config_path();
 
         if !path.exists() {
             write_new_config_file(&path);
@@ -32,6 +86,116 @@ impl Config {
         let mins = ((self.hours_week / 5.).fract() * 60.) as i64;
         Span::new().hours(hours).minutes(mins)
     }
+
+    pub fn get_export_dir(&self) -> PathBuf {
+        match &self.export_dir {
+            Some(export_dir) if export_dir.is_dir() => export_dir.clone(),
+            _ => default_export_dir(),
+        }
+    }
+
+    pub fn get_export_file_name(&self) -> String {
+        let today = Zoned::now().date().to_string();
+        self.export_file_pattern.replace("{date}", &today)
+    }
+
+    // remembers directory and file name of an export, today's date in the name becomes `{date}` again
+    pub fn remember_export_path(&mut self, path: &Path) {
+        if let Some(parent) = path.parent() {
+            self.export_dir = Some(parent.to_path_buf());
+        }
+        if let Some(file_name) = path.file_name() {
+            let today = Zoned::now().date().to_string();
+            self.export_file_pattern = file_name.to_string_lossy().replace(&today, "{date}");
+        }
+        self.update_file(|content| self.with_export_path(content));
+    }
+
+    // writes just the theme, the window geometry and the mini mode into the file on disk,
+    // keeping everything else as it is there, e.g. the export path saved by `--export` or hand edits
+    pub fn save_appearance(&self) {
+        self.update_file(|content| self.with_appearance(content));
+    }
+
+    pub(crate) fn with_appearance(&self, content: &str) -> String {
+        let mut lines = vec![];
+        if self.theme != ThemeChoice::default() {
+            lines.push(format!("theme = {}", self.theme));
+        }
+        if let Some((width, height)) = self.window_size {
+            lines.push(format!("window_size = {width}x{height}"));
+        }
+        if let Some((x, y)) = self.window_position {
+            lines.push(format!("window_position = {x},{y}"));
+        }
+        if self.compact {
+            lines.push("compact = true".to_owned());
+        }
+        replace_keys(content, &["theme", "window_size", "window_position", "compact"], lines)
+    }
+
+    pub(crate) fn with_export_path(&self, content: &str) -> String {
+        let mut lines = vec![];
+        if let Some(export_dir) = &self.export_dir {
+            lines.push(format!("export_dir = {}", export_dir.display()));
+        }
+        if self.export_file_pattern != DEFAULT_EXPORT_FILE_PATTERN {
+            lines.push(format!("export_file_pattern = {}", self.export_file_pattern));
+        }
+        replace_keys(content, &["export_dir", "export_file_pattern"], lines)
+    }
+
+    // the gui and the command line each change only their own keys, the user may have edited the rest meanwhile
+    fn update_file(&self, update: impl FnOnce(&str) -> String) {
+        let path = config_path();
+        let content = fs::read_to_string(&path).unwrap_or_default();
+        if let Err(error) = fs::write(&path, update(&content)) {
+            println!("ERROR ::: could not save the config: {error}");
+        }
+    }
+}
+
+// drops the lines setting one of `keys` and appends `lines` in their place
+fn replace_keys(content: &str, keys: &[&str], lines: Vec<String>) -> String {
+    content
+        .lines()
+        .filter(|line| !line.split_once('=').is_some_and(|(key, _)| keys.contains(&key.trim())))
+        .map(str::to_owned)
+        .chain(lines)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn config_path() -> PathBuf {
+    let mut path = env::current_exe().unwrap();
+    path.set_file_name(".config");
+    path.set_extension("txt");
+    path
+}
+
+fn default_export_dir() -> PathBuf {
+    dirs::document_dir()
+        .filter(|dir| dir.is_dir())
+        .or_else(dirs::home_dir)
+        .unwrap_or_else(|| env::current_dir().unwrap())
+}
+
+// only characters that are safe in a file name on every platform
+fn default_device_name() -> String {
+    let host_name = fs::read_to_string("/etc/hostname")
+        .ok()
+        .or_else(|| env::var("HOSTNAME").ok())
+        .or_else(|| env::var("COMPUTERNAME").ok())
+        .unwrap_or_default();
+    let device_name: String = host_name
+        .trim()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    match device_name.is_empty() {
+        true => "device".to_owned(),
+        false => device_name,
+    }
 }
 
 fn write_new_config_file(path: &PathBuf) {
@@ -40,7 +204,7 @@ fn write_new_config_file(path: &PathBuf) {
     let content = content.as_bytes();
 
     let mut file = File::create(path).unwrap();
-    let _ = file.write_all(content.into());
+    let _ = file.write_all(content);
 }
 
 
@@ -50,13 +214,38 @@ fn read_config_file(path: &PathBuf) -> Config {
 
     let mut config = Config {
         hours_week: 0.,
-        start_date: date(2024, 07, 01),
+        start_date: date(2024, 7, 1),
+        export_dir: None,
+        export_file_pattern: DEFAULT_EXPORT_FILE_PATTERN.to_owned(),
+        employee_name: String::new(),
+        api_port: None,
+        sync_dir: None,
+        device_name: default_device_name(),
+        encrypt_data: false,
+        idle_minutes: Some(DEFAULT_IDLE_MINUTES),
+        stop_on_suspend: true,
+        stop_on_lock: false,
+        start_on_resume: false,
+        notifications: true,
+        clock_in_reminder: Some(DEFAULT_CLOCK_IN_REMINDER),
+        running_reminder: Some(DEFAULT_RUNNING_REMINDER),
+        max_daily_hours: Some(DEFAULT_MAX_DAILY_HOURS),
+        break_after_hours: Some(DEFAULT_BREAK_AFTER_HOURS),
+        focus_minutes: DEFAULT_FOCUS_MINUTES,
+        focus_break_minutes: DEFAULT_FOCUS_BREAK_MINUTES,
+        language: Language::from_env(),
+        theme: ThemeChoice::default(),
+        window_size: None,
+        window_position: None,
+        compact: false,
     };
 
     for line in content.lines() {
         let key_val = line.split_once("=");
         if let Some((key, val)) = key_val  {
             let key = key.to_string().replace(" ", "");
+            // paths and file names may contain spaces, so only trim them
+            let raw_val = val.trim().to_string();
             let val = val.to_string().replace(" ", "");
 
             if key == "hours_week" {
@@ -65,7 +254,60 @@ fn read_config_file(path: &PathBuf) -> Config {
             if key == "start_date" {
                 config.start_date = val.parse::<Date>().unwrap();
             }
+            if key == "export_dir" && !raw_val.is_empty() {
+                config.export_dir = Some(PathBuf::from(raw_val));
+            } else if key == "export_file_pattern" && !raw_val.is_empty() {
+                config.export_file_pattern = raw_val;
+            } else if key == "employee_name" {
+                config.employee_name = raw_val;
+            } else if key == "api_port" {
+                config.api_port = val.parse::<u16>().ok();
+            } else if key == "sync_dir" && !raw_val.is_empty() {
+                config.sync_dir = Some(PathBuf::from(raw_val));
+            } else if key == "device_name" && !val.is_empty() {
+                config.device_name = val;
+            } else if key == "encrypt_data" {
+                config.encrypt_data = val == "true";
+            } else if key == "stop_on_suspend" {
+                config.stop_on_suspend = val != "false";
+            } else if key == "stop_on_lock" {
+                config.stop_on_lock = val == "true";
+            } else if key == "start_on_resume" {
+                config.start_on_resume = val == "true";
+            } else if key == "idle_minutes" {
+                config.idle_minutes = val.parse::<u32>().ok().filter(|minutes| *minutes > 0);
+            } else if key == "notifications" {
+                config.notifications = val != "false";
+            } else if key == "clock_in_reminder" {
+                config.clock_in_reminder = val.parse::<Time>().ok();
+            } else if key == "running_reminder" {
+                config.running_reminder = val.parse::<Time>().ok();
+            } else if key == "max_daily_hours" {
+                config.max_daily_hours = val.parse::<f32>().ok().filter(|hours| *hours > 0.);
+            } else if key == "break_after_hours" {
+                config.break_after_hours = val.parse::<f32>().ok().filter(|hours| *hours > 0.);
+            } else if key == "focus_minutes" {
+                config.focus_minutes = val.parse::<u32>().ok().filter(|minutes| *minutes > 0).unwrap_or(DEFAULT_FOCUS_MINUTES);
+            } else if key == "focus_break_minutes" {
+                config.focus_break_minutes = val.parse::<u32>().unwrap_or(DEFAULT_FOCUS_BREAK_MINUTES);
+            } else if key == "theme" {
+                config.theme = ThemeChoice::parse(&val).unwrap_or_default();
+            } else if key == "window_size" {
+                config.window_size = parse_pair(&val, 'x');
+            } else if key == "window_position" {
+                config.window_position = parse_pair(&val, ',');
+            } else if key == "compact" {
+                config.compact = val == "true";
+            } else if key == "language" {
+                config.language = Language::parse(&val).unwrap_or(Language::from_env());
+            }
         }
     }
     config
-}
\ No newline at end of file
+}
+
+// `500x300` or `120,80`
+fn parse_pair(val: &str, separator: char) -> Option<(f32, f32)> {
+    let (first, second) = val.split_once(separator)?;
+    Some((first.parse::<f32>().ok()?, second.parse::<f32>().ok()?))
+}
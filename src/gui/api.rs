@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use iced::futures::channel::mpsc as async_mpsc;
+use iced::futures::executor::block_on;
+use iced::futures::{SinkExt, Stream};
+use jiff::civil::Date;
+use jiff::Zoned;
+use serde_json::{json, Value};
+
+use crate::gui::corrections::collect_corrections;
+use crate::gui::gui_main::{App, Message};
+use crate::gui::range_report::{build_range_report, ReportDay};
+use crate::gui::serialize::sorted_work_days;
+use crate::utils::{compute_should_hours, format_iso_timestamp, span_in_hours};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiRequest {
+    pub method: String,
+    pub path: String,
+    pub query: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl ApiResponse {
+    pub fn ok(body: Value) -> ApiResponse {
+        ApiResponse { status: 200, body }
+    }
+
+    pub fn error(status: u16, message: &str) -> ApiResponse {
+        ApiResponse { status, body: json!({ "error": message }) }
+    }
+}
+
+// a request travelling through the iced update loop, the server thread waits for the answer
+#[derive(Debug, Clone)]
+pub struct ApiCall {
+    pub request: ApiRequest,
+    responder: Arc<Mutex<Option<mpsc::Sender<ApiResponse>>>>,
+}
+
+impl ApiCall {
+    pub fn respond(&self, response: ApiResponse) {
+        if let Some(responder) = self.responder.lock().unwrap().take() {
+            let _ = responder.send(response);
+        }
+    }
+}
+
+// hands a request to the update loop and waits for the answer, used by the http api and the control socket
+pub fn dispatch(output: &mut async_mpsc::Sender<Message>, request: ApiRequest) -> ApiResponse {
+    let (sender, receiver) = mpsc::channel();
+    let call = ApiCall {
+        request,
+        responder: Arc::new(Mutex::new(Some(sender))),
+    };
+    match block_on(output.send(Message::Api(call))) {
+        Ok(()) => receiver
+            .recv_timeout(Duration::from_secs(5))
+            .unwrap_or(ApiResponse::error(503, "the app did not answer")),
+        Err(_) => ApiResponse::error(503, "the app is shutting down"),
+    }
+}
+
+// runs the server on its own thread and hands every request to the app as `Message::Api`
+pub fn api_stream(port: u16) -> impl Stream<Item = Message> {
+    iced::stream::channel(16, move |output| async move {
+        thread::spawn(move || serve(port, output));
+        std::future::pending::<()>().await
+    })
+}
+
+fn serve(port: u16, output: async_mpsc::Sender<Message>) {
+    let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, port)) {
+        Ok(listener) => listener,
+        Err(error) => {
+            println!("ERROR ::: api could not listen on port {port}: {error}");
+            return;
+        }
+    };
+
+    for stream in listener.incoming().flatten() {
+        let mut output = output.clone();
+        thread::spawn(move || handle_connection(stream, port, &mut output));
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, port: u16, output: &mut async_mpsc::Sender<Message>) {
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+    let mut buffer = [0; 8192];
+    let mut read = 0;
+    while read < buffer.len() {
+        match stream.read(&mut buffer[read..]) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => read += n,
+        }
+        if buffer[..read].windows(4).any(|window| window == b"\r\n\r\n") {
+            break;
+        }
+    }
+    let head = String::from_utf8_lossy(&buffer[..read]);
+
+    let response = match (parse_request(&head), forbidden(&head, port)) {
+        (Some(_), Some(reason)) => ApiResponse::error(403, reason),
+        (Some(request), None) => dispatch(output, request),
+        (None, _) => ApiResponse::error(400, "malformed request"),
+    };
+    write_response(&mut stream, &response);
+}
+
+pub fn parse_request(head: &str) -> Option<ApiRequest> {
+    let request_line = head.lines().next()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_uppercase();
+    let target = parts.next()?;
+    if !parts.next()?.starts_with("HTTP/") {
+        return None;
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (key.to_owned(), decode_query_value(value))
+        })
+        .collect();
+    Some(ApiRequest {
+        method,
+        path: path.trim_end_matches('/').to_owned(),
+        query,
+    })
+}
+
+// `+` and `%20` are spaces, invalid escapes are kept as they are
+fn decode_query_value(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = value.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (b'+', _) => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+// browsers add an Origin header to cross site requests, a web page must not clock us in or out.
+// a page whose domain was rebound to 127.0.0.1 sends its own name as Host and can't read our answers
+pub fn forbidden(head: &str, port: u16) -> Option<&'static str> {
+    if header(head, "origin").is_some() {
+        return Some("requests from web pages are not allowed");
+    }
+    let host = header(head, "host").unwrap_or_default();
+    match [format!("127.0.0.1:{port}"), format!("localhost:{port}")].iter().any(|allowed| host.eq_ignore_ascii_case(allowed)) {
+        true => None,
+        false => Some("the host must be 127.0.0.1 or localhost with the port"),
+    }
+}
+
+fn header<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    head.lines()
+        .skip(1)
+        .filter_map(|line| line.split_once(':'))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.trim())
+}
+
+fn write_response(stream: &mut TcpStream, response: &ApiResponse) {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        423 => "Locked",
+        _ => "Service Unavailable",
+    };
+    let body = serde_json::to_string_pretty(&response.body).unwrap();
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        response.status,
+        body.len()
+    );
+}
+
+// GET  /status             running state, today's hours and the contingent
+// POST /start, /stop       clock in or out, answers with the new status
+// GET  /today              today's intervals as a json report
+// GET  /report?from=&to=   json report of a date range, see `RangeReport`
+// GET  /corrections?from=&to=  manual corrections of intervals, see `CorrectionLine`
+// GET  /search?q=          days whose notes or labels contain the text, as `ReportDay`s
+pub fn handle_request(app: &mut App, request: &ApiRequest) -> ApiResponse {
+    let today = Zoned::now().date();
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/status") => ApiResponse::ok(status(app)),
+        ("POST", "/start") => match app.start_work() {
+            true => ApiResponse::ok(status(app)),
+            false => ApiResponse::error(409, "already started"),
+        },
+        ("POST", "/stop") => match app.stop_work() {
+            true => ApiResponse::ok(status(app)),
+            false => ApiResponse::error(409, "not started"),
+        },
+        ("GET", "/today") => report(app, Some(today), Some(today)),
+        ("GET", "/report") => {
+            let date = |key: &str| request.query.get(key).map(|value| value.parse::<Date>());
+            match (date("from").transpose(), date("to").transpose()) {
+                (Ok(from), Ok(to)) => report(app, from, to),
+                _ => ApiResponse::error(400, "from and to must be dates like 2024-11-04"),
+            }
+        }
+        ("GET", "/corrections") => {
+            let date = |key: &str| request.query.get(key).map(|value| value.parse::<Date>());
+            match (date("from").transpose(), date("to").transpose()) {
+                (Ok(from), Ok(to)) => {
+                    let corrections = collect_corrections(&sorted_work_days(&app.calendar), from, to);
+                    ApiResponse::ok(serde_json::to_value(corrections).unwrap())
+                }
+                _ => ApiResponse::error(400, "from and to must be dates like 2024-11-04"),
+            }
+        }
+        ("GET", "/search") => match request.query.get("q").filter(|query| !query.trim().is_empty()) {
+            Some(query) => search(app, query),
+            None => ApiResponse::error(400, "q must contain the text to search for"),
+        },
+        (_, "/status" | "/start" | "/stop" | "/today" | "/report" | "/corrections" | "/search") => ApiResponse::error(405, "method not allowed"),
+        _ => ApiResponse::error(404, "unknown endpoint"),
+    }
+}
+
+fn status(app: &App) -> Value {
+    let today = Zoned::now().date();
+    let todays_work = app.calendar.get(&today.to_string());
+    let running_since = todays_work
+        .and_then(|work_day| work_day.work_duration.last())
+        .filter(|work_times| work_times.end.is_none())
+        .and_then(|work_times| work_times.start.as_ref())
+        .map(format_iso_timestamp);
+    let worked = todays_work.and_then(|work_day| work_day.sum_work.as_ref()).map(span_in_hours).unwrap_or(0.);
+    let breaks = todays_work.and_then(|work_day| work_day.sum_pause.as_ref()).map(span_in_hours).unwrap_or(0.);
+    let contingent = app.total_worked_hours() - compute_should_hours(app.config.start_date, today, &app.config);
+
+    json!({
+        "running": app.is_running(),
+        "running_since": running_since,
+        "date": today,
+        "worked_hours": round(worked),
+        "break_hours": round(breaks),
+        "contingent_hours": round(contingent),
+    })
+}
+
+fn report(app: &App, from: Option<Date>, to: Option<Date>) -> ApiResponse {
+    let work_days = sorted_work_days(&app.calendar);
+    let report = build_range_report(&app.config, &work_days, from, to);
+    ApiResponse::ok(serde_json::to_value(report).unwrap())
+}
+
+fn search(app: &App, query: &str) -> ApiResponse {
+    let work_days = sorted_work_days(&app.calendar);
+    let report = build_range_report(&app.config, &work_days, None, None);
+    let days: Vec<&ReportDay> = report
+        .days
+        .iter()
+        .filter(|day| app.calendar.get(&day.date.to_string()).is_some_and(|work_day| work_day.matches_text(query)))
+        .collect();
+    ApiResponse::ok(serde_json::to_value(days).unwrap())
+}
+
+fn round(hours: f32) -> f64 {
+    (hours as f64 * 100.).round() / 100.
+}
@@ -0,0 +1,164 @@
+use std::thread;
+
+use iced::futures::executor::block_on;
+use iced::futures::{SinkExt, Stream};
+use iced::{event, window, Event, Point, Size, Theme};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::gui::gui_main::Message;
+
+pub const DEFAULT_WINDOW_SIZE: Size = Size::new(500., 300.);
+// just the timer and the buttons, see `compact_view`
+pub const COMPACT_WINDOW_SIZE: Size = Size::new(320., 60.);
+
+// the config's `theme`, a built-in iced theme by its name or the desktop's light or dark preference
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThemeChoice {
+    System,
+    Named(String),
+}
+
+impl Default for ThemeChoice {
+    fn default() -> Self {
+        ThemeChoice::Named(Theme::TokyoNightStorm.to_string())
+    }
+}
+
+impl ThemeChoice {
+    // `system` or the name as listed by iced, ignoring case and spaces
+    pub fn parse(input: &str) -> Option<ThemeChoice> {
+        let input = input.replace(' ', "").to_lowercase();
+        if input == "system" {
+            return Some(ThemeChoice::System);
+        }
+        Theme::ALL
+            .iter()
+            .map(Theme::to_string)
+            .find(|name| name.replace(' ', "").to_lowercase() == input)
+            .map(ThemeChoice::Named)
+    }
+
+    // every built-in theme after following the system
+    pub fn all() -> Vec<ThemeChoice> {
+        let named = Theme::ALL.iter().map(|theme| ThemeChoice::Named(theme.to_string()));
+        [ThemeChoice::System].into_iter().chain(named).collect()
+    }
+
+    // an unknown name falls back to the default theme
+    pub fn theme(&self, system_dark: bool) -> Theme {
+        match self {
+            ThemeChoice::System if system_dark => Theme::Dark,
+            ThemeChoice::System => Theme::Light,
+            ThemeChoice::Named(name) => Theme::ALL
+                .iter()
+                .find(|theme| theme.to_string() == *name)
+                .cloned()
+                .unwrap_or(Theme::TokyoNightStorm),
+        }
+    }
+}
+
+impl std::fmt::Display for ThemeChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThemeChoice::System => write!(f, "System"),
+            ThemeChoice::Named(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+// the size and position the window opens with, the last ones used unless the mini mode was left on
+pub fn initial_size(config: &Config) -> Size {
+    match (config.compact, config.window_size) {
+        (true, _) => COMPACT_WINDOW_SIZE,
+        (false, Some((width, height))) => Size::new(width, height),
+        (false, None) => DEFAULT_WINDOW_SIZE,
+    }
+}
+
+pub fn initial_position(config: &Config) -> window::Position {
+    match config.window_position {
+        Some((x, y)) => window::Position::Specific(Point::new(x, y)),
+        None => window::Position::default(),
+    }
+}
+
+// the size to return to when the mini mode is left
+pub fn full_size(config: &Config) -> Size {
+    initial_size(&Config { compact: false, ..config.clone() })
+}
+
+// moves and resizes of the window, remembered in the config. wayland doesn't report the position
+pub fn window_geometry(event: Event, _status: event::Status, _id: window::Id) -> Option<Message> {
+    match event {
+        Event::Window(window::Event::Resized(size)) => Some(Message::WindowResized(size)),
+        Event::Window(window::Event::Moved(position)) => Some(Message::WindowMoved(position)),
+        _ => None,
+    }
+}
+
+// sends `Message::SystemThemeChanged` with the desktop's preference and again whenever it changes,
+// nothing where the xdg desktop portal is missing
+pub fn system_theme_stream() -> impl Stream<Item = Message> {
+    iced::stream::channel(1, |mut output| async move {
+        thread::spawn(move || {
+            let send = |dark| block_on(output.send(Message::SystemThemeChanged(dark))).is_ok();
+            #[cfg(target_os = "linux")]
+            if let Err(error) = portal::listen(send) {
+                println!("WARN ::: no color scheme from the desktop portal: {error}");
+            }
+            #[cfg(not(target_os = "linux"))]
+            drop(send);
+        });
+        std::future::pending::<()>().await
+    })
+}
+
+#[cfg(target_os = "linux")]
+mod portal {
+    use zbus::blocking::{Connection, MessageIterator};
+    use zbus::message::Type;
+    use zbus::zvariant::{OwnedValue, Value};
+    use zbus::MatchRule;
+
+    const PORTAL: &str = "org.freedesktop.portal.Desktop";
+    const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+    const SETTINGS: &str = "org.freedesktop.portal.Settings";
+    const APPEARANCE: &str = "org.freedesktop.appearance";
+    const COLOR_SCHEME: &str = "color-scheme";
+
+    // blocks until the connection to the session bus ends or `send` returns false
+    pub fn listen(mut send: impl FnMut(bool) -> bool) -> zbus::Result<()> {
+        let connection = Connection::session()?;
+        let rule = MatchRule::builder()
+            .msg_type(Type::Signal)
+            .interface(SETTINGS)?
+            .member("SettingChanged")?
+            .build();
+        let messages = MessageIterator::for_match_rule(rule, &connection, None)?;
+
+        let reply = connection.call_method(Some(PORTAL), PORTAL_PATH, Some(SETTINGS), "Read", &(APPEARANCE, COLOR_SCHEME))?;
+        let scheme: OwnedValue = reply.body().deserialize()?;
+        if !send(is_dark(&scheme)) {
+            return Ok(());
+        }
+        for message in messages {
+            let message = message?;
+            let (namespace, key, value) = message.body().deserialize::<(String, String, OwnedValue)>()?;
+            if namespace == APPEARANCE && key == COLOR_SCHEME && !send(is_dark(&value)) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // 1 prefers dark, 2 prefers light and 0 has no preference. `Read` wraps the value in a second variant
+    fn is_dark(value: &Value) -> bool {
+        match value {
+            Value::Value(inner) => is_dark(inner),
+            Value::U32(scheme) => *scheme == 1,
+            _ => false,
+        }
+    }
+}
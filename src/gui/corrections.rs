@@ -0,0 +1,111 @@
+use std::fs;
+
+use jiff::civil::Date;
+use jiff::tz::TimeZone;
+use jiff::Zoned;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::gui::gui_logic::{OneDaysWork, WorkTimes};
+use crate::utils::format_iso_timestamp;
+
+// one correction of the report, times are ISO 8601 with offset like in `RangeReport`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrectionLine {
+    pub date: Date,
+    pub label: Option<String>,
+    pub corrected_at: String,
+    pub original_start: Option<String>,
+    pub original_end: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub reason: String,
+}
+
+// all corrections of intervals recorded on days between `from` and `to`, ordered by day and time of correction
+pub fn collect_corrections(work_days: &[OneDaysWork], from: Option<Date>, to: Option<Date>) -> Vec<CorrectionLine> {
+    let mut lines = vec![];
+    let in_range = |date: Date| from.is_none_or(|from| date >= from) && to.is_none_or(|to| date <= to);
+    for work_day in work_days.iter().filter(|work_day| in_range(work_day.date)) {
+        let mut day_lines: Vec<CorrectionLine> = work_day
+            .work_duration
+            .iter()
+            .flat_map(|work_times| correction_lines(work_day.date, work_times))
+            .collect();
+        // ISO timestamps in the same zone sort chronologically
+        day_lines.sort_by(|a, b| a.corrected_at.cmp(&b.corrected_at));
+        lines.extend(day_lines);
+    }
+    lines
+}
+
+pub fn correction_lines(date: Date, work_times: &WorkTimes) -> Vec<CorrectionLine> {
+    work_times
+        .corrections
+        .iter()
+        .map(|correction| CorrectionLine {
+            date,
+            label: work_times.label.clone(),
+            corrected_at: format_iso_timestamp(&correction.corrected_at.to_zoned(TimeZone::system())),
+            original_start: correction.original_start.as_ref().map(format_iso_timestamp),
+            original_end: correction.original_end.as_ref().map(format_iso_timestamp),
+            start: correction.start.as_ref().map(format_iso_timestamp),
+            end: correction.end.as_ref().map(format_iso_timestamp),
+            reason: correction.reason.clone(),
+        })
+        .collect()
+}
+
+pub fn write_corrections_csv(lines: &[CorrectionLine]) -> String {
+    let mut csv = String::from("DATE;LABEL;CORRECTED AT;ORIGINAL START;ORIGINAL END;START;END;REASON\n");
+    let field = |value: &Option<String>| value.clone().unwrap_or_default();
+    for line in lines {
+        csv += &format!(
+            "{};{};{};{};{};{};{};\"{}\"\n",
+            line.date,
+            field(&line.label),
+            line.corrected_at,
+            field(&line.original_start),
+            field(&line.original_end),
+            field(&line.start),
+            field(&line.end),
+            line.reason.replace('"', "\"\""),
+        );
+    }
+    csv
+}
+
+// asks for a file and writes the corrections of the selected date's month
+pub fn export_corrections(config: &Config, work_days: &[OneDaysWork], selected: Date) {
+    let path_buf = rfd::FileDialog::new()
+        .add_filter("CSV", &["csv"])
+        .set_file_name(format!("corrections_{}.csv", selected.strftime("%Y-%m")))
+        .set_directory(config.get_export_dir())
+        .save_file();
+
+    if let Some(path_buf) = path_buf {
+        let lines = collect_corrections(work_days, Some(selected.first_of_month()), Some(selected.last_of_month()));
+        if let Err(error) = fs::write(path_buf, write_corrections_csv(&lines)) {
+            println!("ERROR ::: export of corrections failed: {error}");
+        }
+    }
+}
+
+// the end of an interval started at `start`, a time before the start is on the next day.
+// used for intervals left running, work until after midnight can't be entered otherwise
+pub fn parse_end_after(input: &str, start: &Zoned) -> Result<Zoned, String> {
+    let end = parse_corrected_time(input, start)?;
+    match end <= *start {
+        true => end.tomorrow().map_err(|error| error.to_string()),
+        false => Ok(end),
+    }
+}
+
+// a time entered in the correction form, `08:15` on the interval's date and in its time zone
+pub fn parse_corrected_time(input: &str, like: &Zoned) -> Result<Zoned, String> {
+    let time = jiff::civil::Time::strptime("%H:%M", input.trim()).map_err(|_| format!("{input} is not a time like 08:15"))?;
+    like.date()
+        .to_datetime(time)
+        .to_zoned(like.time_zone().clone())
+        .map_err(|error| error.to_string())
+}
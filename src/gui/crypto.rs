@@ -0,0 +1,242 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::gui::journal::{journal_path, seal_journal};
+use crate::gui::serialize::{read_calendar, Calendar};
+
+const FORMAT: &str = "time_tracker_encrypted";
+const VERSION: u32 = 1;
+// binds the ciphertext to this file format, a ciphertext copied into another context fails to open
+const ASSOCIATED_DATA: &[u8] = b"time_tracker_encrypted v1";
+// journal lines are encrypted one by one and start with this prefix
+const LINE_PREFIX: &str = "enc:";
+
+// what an encrypted data file contains instead of the calendar json
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    format: String,
+    version: u32,
+    kdf: String,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+// the key derived from the passphrase, kept for the whole run like the control socket
+pub struct DataKey {
+    key: [u8; 32],
+    salt: [u8; 16],
+}
+
+impl DataKey {
+    pub fn new(passphrase: &str) -> Result<DataKey, String> {
+        if passphrase.chars().count() < 8 {
+            return Err("the passphrase needs at least 8 characters".to_owned());
+        }
+        let salt: [u8; 16] = random_bytes()?;
+        Ok(DataKey {
+            key: derive_key(passphrase, &salt)?,
+            salt,
+        })
+    }
+
+    // the key of an encrypted file, fails if the passphrase doesn't open it
+    pub fn for_file_content(passphrase: &str, content: &str) -> Result<DataKey, String> {
+        let envelope: Envelope = serde_json::from_str(content).map_err(|_| "the data file is not encrypted".to_owned())?;
+        let salt: [u8; 16] = from_hex(&envelope.salt)
+            .and_then(|salt| salt.try_into().ok())
+            .ok_or("the data file is damaged")?;
+        let data_key = DataKey {
+            key: derive_key(passphrase, &salt)?,
+            salt,
+        };
+        open(&data_key, &envelope.nonce, &envelope.ciphertext)?;
+        Ok(data_key)
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> String {
+        let (nonce, ciphertext) = seal(self, plaintext);
+        let envelope = Envelope {
+            format: FORMAT.to_owned(),
+            version: VERSION,
+            kdf: "argon2id".to_owned(),
+            salt: to_hex(&self.salt),
+            nonce,
+            ciphertext,
+        };
+        serde_json::to_string(&envelope).unwrap()
+    }
+
+    pub fn decrypt(&self, content: &str) -> Result<String, String> {
+        let envelope: Envelope = serde_json::from_str(content).map_err(|_| "the data file is not encrypted".to_owned())?;
+        open(self, &envelope.nonce, &envelope.ciphertext)
+    }
+
+    pub fn encrypt_line(&self, line: &str) -> String {
+        let (nonce, ciphertext) = seal(self, line);
+        format!("{LINE_PREFIX}{nonce}:{ciphertext}")
+    }
+
+    pub fn decrypt_line(&self, line: &str) -> Result<String, String> {
+        let sealed = line.strip_prefix(LINE_PREFIX).ok_or("the line is not encrypted")?;
+        let (nonce, ciphertext) = sealed.split_once(':').ok_or("damaged line")?;
+        open(self, nonce, ciphertext)
+    }
+}
+
+static KEY: Mutex<Option<DataKey>> = Mutex::new(None);
+
+pub fn is_unlocked() -> bool {
+    KEY.lock().unwrap().is_some()
+}
+
+pub fn is_encrypted(content: &str) -> bool {
+    serde_json::from_str::<Envelope>(content).is_ok_and(|envelope| envelope.format == FORMAT)
+}
+
+pub fn is_encrypted_file(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|content| is_encrypted(&content))
+}
+
+// a passphrase is needed if the file is encrypted or encryption is turned on in the config
+pub fn needs_unlock(config: &Config, path: &Path) -> bool {
+    !is_unlocked() && (config.encrypt_data || is_encrypted_file(path))
+}
+
+// checks the passphrase against the encrypted file and keeps the key for later reads and writes
+pub fn unlock(passphrase: &str, path: &Path) -> Result<(), String> {
+    let content = fs::read_to_string(path).map_err(|error| error.to_string())?;
+    *KEY.lock().unwrap() = Some(DataKey::for_file_content(passphrase, &content)?);
+    Ok(())
+}
+
+// starts encrypting with a new passphrase and encrypts the data file and the journal written so far
+pub fn encrypt_existing_data(passphrase: &str, path: &Path) -> Result<(), String> {
+    let calendar = read_calendar(path);
+    *KEY.lock().unwrap() = Some(DataKey::new(passphrase)?);
+    if let Some(calendar) = calendar {
+        Calendar::update(&calendar.into_hashmap(), path);
+    }
+    seal_journal(&journal_path(path)).map_err(|error| error.to_string())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8; 16]) -> Result<[u8; 32], String> {
+    let mut key = [0; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|error| error.to_string())?;
+    Ok(key)
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N], String> {
+    let mut bytes = [0; N];
+    getrandom::getrandom(&mut bytes).map_err(|error| error.to_string())?;
+    Ok(bytes)
+}
+
+// returns nonce and ciphertext as hex
+fn seal(data_key: &DataKey, plaintext: &str) -> (String, String) {
+    let nonce: [u8; 24] = random_bytes().expect("no random numbers available");
+    let cipher = XChaCha20Poly1305::new(&data_key.key.into());
+    let payload = Payload {
+        msg: plaintext.as_bytes(),
+        aad: ASSOCIATED_DATA,
+    };
+    let ciphertext = cipher.encrypt(XNonce::from_slice(&nonce), payload).expect("encryption failed");
+    (to_hex(&nonce), to_hex(&ciphertext))
+}
+
+fn open(data_key: &DataKey, nonce: &str, ciphertext: &str) -> Result<String, String> {
+    let nonce = from_hex(nonce).filter(|nonce| nonce.len() == 24).ok_or("the data file is damaged")?;
+    let ciphertext = from_hex(ciphertext).ok_or("the data file is damaged")?;
+    let cipher = XChaCha20Poly1305::new(&data_key.key.into());
+    let payload = Payload {
+        msg: &ciphertext,
+        aad: ASSOCIATED_DATA,
+    };
+    let plaintext = cipher
+        .decrypt(XNonce::from_slice(&nonce), payload)
+        .map_err(|_| "wrong passphrase or the data file was modified".to_owned())?;
+    String::from_utf8(plaintext).map_err(|error| error.to_string())
+}
+
+// reads the data file, decrypting it if needed
+pub fn read_data(path: &Path) -> io::Result<String> {
+    let content = fs::read_to_string(path)?;
+    if !is_encrypted(&content) {
+        return Ok(content);
+    }
+    match KEY.lock().unwrap().as_ref() {
+        Some(data_key) => data_key.decrypt(&content).map_err(io::Error::other),
+        None => Err(io::Error::new(io::ErrorKind::PermissionDenied, "the data file is encrypted and locked")),
+    }
+}
+
+// writes the data file, encrypted once a passphrase is set. refuses to replace an encrypted file with plaintext
+pub fn write_data(path: &Path, plaintext: &str) -> io::Result<()> {
+    let content = match KEY.lock().unwrap().as_ref() {
+        Some(data_key) => data_key.encrypt(plaintext),
+        None if is_encrypted_file(path) => {
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied, "the data file is encrypted and locked"));
+        }
+        None => plaintext.to_owned(),
+    };
+    fs::write(path, content)
+}
+
+// a journal line, encrypted with the data file's key if there is one
+pub fn seal_line(line: &str) -> String {
+    match KEY.lock().unwrap().as_ref() {
+        Some(data_key) => data_key.encrypt_line(line),
+        None => line.to_owned(),
+    }
+}
+
+pub fn open_line(line: &str) -> Result<String, String> {
+    if !line.starts_with(LINE_PREFIX) {
+        return Ok(line.to_owned());
+    }
+    match KEY.lock().unwrap().as_ref() {
+        Some(data_key) => data_key.decrypt_line(line),
+        None => Err("the journal is encrypted and locked".to_owned()),
+    }
+}
+
+// for the command line, the passphrase comes from TIME_TRACKER_PASSPHRASE or is read from stdin.
+// a new passphrase is only chosen in the gui where it has to be typed twice, a typo here would lock the data for good
+pub fn unlock_from_terminal(config: &Config, path: &Path) -> Result<(), String> {
+    if !needs_unlock(config, path) {
+        return Ok(());
+    }
+    if !is_encrypted_file(path) {
+        return Err("the data file isn't encrypted yet, open the app once to choose a passphrase".to_owned());
+    }
+    let passphrase = match std::env::var("TIME_TRACKER_PASSPHRASE") {
+        Ok(passphrase) => passphrase,
+        // typed without echo, so it doesn't end up on the screen or in the scrollback
+        Err(_) => rpassword::prompt_password("passphrase: ").map_err(|error| error.to_string())?,
+    };
+    unlock(&passphrase, path)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
@@ -0,0 +1,303 @@
+use jiff::civil::{Date, DateTime, Time};
+use jiff::tz::TimeZone;
+use jiff::Zoned;
+use serde::{Deserialize, Serialize};
+
+use crate::gui::gui_logic::WorkTimes;
+use crate::gui::ical::{parse_location, ImportedInterval};
+use crate::i18n::in_any_language;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ImportFormat {
+    Ical,
+    OwnCsv,
+    GenericCsv,
+    Toggl,
+    Clockify,
+    Kimai,
+}
+
+impl std::fmt::Display for ImportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ImportFormat::Ical => "iCalendar",
+                ImportFormat::OwnCsv => "time tracker csv",
+                ImportFormat::GenericCsv => "csv",
+                ImportFormat::Toggl => "Toggl csv",
+                ImportFormat::Clockify => "Clockify csv",
+                ImportFormat::Kimai => "Kimai csv",
+            }
+        )
+    }
+}
+
+// everything an import file contains, vacation days and day notes only come from our own csv export
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportedData {
+    pub intervals: Vec<ImportedInterval>,
+    pub vacation_days: Vec<Date>,
+    #[serde(default)]
+    pub day_notes: Vec<(Date, String)>,
+    // events of an ical file that couldn't be turned into an interval
+    #[serde(default)]
+    pub skipped: usize,
+}
+
+// which column holds what, found by the header names
+#[derive(Default)]
+struct Columns {
+    start_date: Option<usize>,
+    start_time: Option<usize>,
+    end_date: Option<usize>,
+    end_time: Option<usize>,
+    labels: Vec<usize>,
+    location: Option<usize>,
+    note: Option<usize>,
+}
+
+pub fn parse_csv(content: &str) -> Result<(ImportFormat, ImportedData), String> {
+    let content = content.trim_start_matches('\u{feff}');
+    let records = split_csv_records(content);
+    let Some(&(_, header)) = records.iter().find(|(_, record)| !record.trim().is_empty()) else {
+        return Err("the file is empty".to_owned());
+    };
+
+    if header.starts_with(";;;;;;") || content.lines().any(is_interval_heading) {
+        return parse_own_csv(content).map(|data| (ImportFormat::OwnCsv, data));
+    }
+
+    let delimiter = [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|delimiter| header.matches(*delimiter).count())
+        .unwrap();
+    let headings: Vec<String> = split_csv_line(header, delimiter)
+        .iter()
+        .map(|heading| heading.trim().to_lowercase())
+        .collect();
+    let has = |name: &str| headings.iter().any(|heading| heading == name);
+
+    let format = if has("duration (decimal)") || has("duration (h)") {
+        ImportFormat::Clockify
+    } else if has("start date") && has("email") {
+        ImportFormat::Toggl
+    } else if has("from") && has("to") && has("activity") {
+        ImportFormat::Kimai
+    } else {
+        ImportFormat::GenericCsv
+    };
+
+    let find = |names: &[&str]| names.iter().find_map(|name| headings.iter().position(|heading| heading == name));
+    let columns = Columns {
+        start_date: find(&["date", "start date", "begin date"]),
+        start_time: find(&["start", "start time", "from", "begin"]),
+        end_date: find(&["end date"]),
+        end_time: find(&["end", "end time", "to"]),
+        labels: ["label", "project", "description", "activity"]
+            .iter()
+            .filter_map(|name| find(&[name]))
+            .collect(),
+        location: find(&["location"]),
+        note: find(&["note", "notes", "comment"]),
+    };
+    if columns.start_time.is_none() || columns.end_time.is_none() {
+        return Err(format!("no start and end columns found in header: {header}"));
+    }
+
+    let mut data = ImportedData::default();
+    for &(line_number, line) in records.iter().skip_while(|(_, record)| *record != header).skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = split_csv_line(line, delimiter);
+        let field = |column: Option<usize>| column.and_then(|column| fields.get(column)).map(|field| field.trim()).unwrap_or("");
+        let error = |message: String| format!("line {}: {message}", line_number + 1);
+
+        let (start, end) = parse_start_end(
+            field(columns.start_date),
+            field(columns.start_time),
+            field(columns.end_date),
+            field(columns.end_time),
+        )
+        .map_err(error)?;
+
+        let label = columns
+            .labels
+            .iter()
+            .map(|column| field(Some(*column)))
+            .find(|label| !label.is_empty())
+            .unwrap_or("Work");
+        let mut work_times = WorkTimes::init();
+        work_times.set_label(label);
+        work_times.start = Some(start);
+        work_times.end = Some(end);
+        work_times.note = field(columns.note).to_owned();
+        data.intervals.push(ImportedInterval {
+            work_times,
+            location: parse_location(field(columns.location)),
+        });
+    }
+    Ok((format, data))
+}
+
+// the `;START;END;;DURATION;BREAK;NOTE` line above the intervals of a day, in any language
+fn is_interval_heading(line: &str) -> bool {
+    let mut fields = line.split(';').skip(1);
+    fields.next().is_some_and(|field| in_any_language(field, "START")) && fields.next().is_some_and(|field| in_any_language(field, "END"))
+}
+
+// reads the day blocks written by `serialize_to_csv`
+fn parse_own_csv(content: &str) -> Result<ImportedData, String> {
+    let mut data = ImportedData::default();
+    let mut date: Option<Date> = None;
+    let mut location = None;
+
+    for (line_number, line) in content.lines().enumerate() {
+        let fields: Vec<&str> = line.split(';').collect();
+        let first = fields[0].trim();
+
+        if let Ok(day) = first.parse::<Date>() {
+            date = Some(day);
+            location = None;
+            if let Some(note) = fields.get(6).map(|note| note.trim()).filter(|note| !note.is_empty()) {
+                data.day_notes.push((day, note.to_owned()));
+            }
+        } else if fields.get(1).is_some_and(|field| in_any_language(field, "SUM WORK")) {
+            location = parse_location(first);
+        } else if in_any_language(first, "VACATION") {
+            data.vacation_days.extend(date);
+        } else if let (Some(date), Some(start), Some(end)) = (date, fields.get(1), fields.get(2)) {
+            if start.is_empty() || end.is_empty() || is_interval_heading(line) {
+                continue;
+            }
+            let (start, end) = parse_start_end(&date.to_string(), start, "", end)
+                .map_err(|message| format!("line {}: {message}", line_number + 1))?;
+            let mut work_times = WorkTimes::init();
+            work_times.set_label("Work");
+            work_times.start = Some(start);
+            work_times.end = Some(end);
+            work_times.note = fields.get(6).map(|note| note.trim().to_owned()).unwrap_or_default();
+            data.intervals.push(ImportedInterval { work_times, location });
+        }
+    }
+    Ok(data)
+}
+
+// start and end either come as separate date and time columns or as full date times,
+// an end before the start without an end date is taken to be on the next day
+fn parse_start_end(start_date: &str, start_time: &str, end_date: &str, end_time: &str) -> Result<(Zoned, Zoned), String> {
+    let start = parse_date_time(start_date, start_time)?;
+    let end = match end_date.is_empty() && parse_time(end_time).is_some() {
+        true => {
+            let end = parse_date_time(&start.date().to_string(), end_time)?;
+            if end < start {
+                end.tomorrow().map_err(|error| error.to_string())?
+            } else {
+                end
+            }
+        }
+        false => parse_date_time(end_date, end_time)?,
+    };
+    if end <= start {
+        return Err(format!("end {end_time} is not after start {start_time}"));
+    }
+    Ok((start, end))
+}
+
+fn parse_date_time(date: &str, time: &str) -> Result<Zoned, String> {
+    let date_time = match (parse_date(date), parse_time(time)) {
+        (Some(date), Some(time)) => date.to_datetime(time),
+        // the time column may hold a full date time like `2024-10-31 08:00` or `2024-10-31T08:00:00`
+        _ => {
+            let (date, time) = time
+                .split_once(['T', ' '])
+                .ok_or(format!("invalid date or time: {date} {time}"))?;
+            match (parse_date(date), parse_time(time)) {
+                (Some(date), Some(time)) => date.to_datetime(time),
+                _ => return Err(format!("invalid date or time: {date} {time}")),
+            }
+        }
+    };
+    local_zoned(date_time)
+}
+
+fn local_zoned(date_time: DateTime) -> Result<Zoned, String> {
+    date_time.to_zoned(TimeZone::system()).map_err(|error| error.to_string())
+}
+
+// ISO, US (`10/31/2024`) and German (`31.10.2024`) dates
+fn parse_date(input: &str) -> Option<Date> {
+    let input = input.trim();
+    if let Ok(date) = input.parse::<Date>() {
+        return Some(date);
+    }
+    for format in ["%m/%d/%Y", "%d.%m.%Y", "%d.%m.%y"] {
+        if let Ok(date) = Date::strptime(format, input) {
+            return Some(date);
+        }
+    }
+    None
+}
+
+// `8:00`, `08:00:00` and `08:00 AM` style times
+fn parse_time(input: &str) -> Option<Time> {
+    let input = input.trim().to_uppercase();
+    for format in ["%H:%M", "%H:%M:%S", "%I:%M %p", "%I:%M:%S %p"] {
+        if let Ok(time) = Time::strptime(format, &input) {
+            return Some(time);
+        }
+    }
+    None
+}
+
+// splits into records at the line breaks outside of quotes, so quoted fields may span lines;
+// every record comes with the index of the line it starts on
+fn split_csv_records(content: &str) -> Vec<(usize, &str)> {
+    let mut records = vec![];
+    let mut start = 0;
+    let mut line_number = 0;
+    let mut record_line = 0;
+    let mut in_quotes = false;
+
+    for (index, c) in content.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '\n' => {
+                if !in_quotes {
+                    records.push((record_line, content[start..index].trim_end_matches('\r')));
+                    start = index + 1;
+                    record_line = line_number + 1;
+                }
+                line_number += 1;
+            }
+            _ => (),
+        }
+    }
+    if start < content.len() {
+        records.push((record_line, content[start..].trim_end_matches('\r')));
+    }
+    records
+}
+
+pub fn split_csv_line(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
@@ -0,0 +1,56 @@
+use std::thread;
+use std::time::Duration;
+
+use iced::futures::executor::block_on;
+use iced::futures::{SinkExt, Stream};
+use jiff::{Span, Timestamp, Zoned};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::gui::gui_main::Message;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FocusPhase {
+    // an interval is running until `ends`
+    Focus,
+    // nothing runs until `ends`
+    Break,
+    // the break is over, the next focus block starts when the user starts it
+    Waiting,
+}
+
+// focus mode alternates fixed length intervals and short breaks, each block started by the user. the intervals are ordinary `WorkTimes`,
+// one that ran its full length is marked as focus block and the break becomes its `pause`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FocusSession {
+    pub phase: FocusPhase,
+    pub ends: Zoned,
+}
+
+impl FocusSession {
+    pub fn focus(config: &Config, now: &Zoned) -> FocusSession {
+        FocusSession {
+            phase: FocusPhase::Focus,
+            ends: now.checked_add(Span::new().minutes(config.focus_minutes)).unwrap(),
+        }
+    }
+
+    pub fn pause(config: &Config, now: &Zoned) -> FocusSession {
+        FocusSession {
+            phase: FocusPhase::Break,
+            ends: now.checked_add(Span::new().minutes(config.focus_break_minutes)).unwrap(),
+        }
+    }
+}
+
+// sends `Message::FocusPhaseEnded` once `ends` is reached, subscribed again for every phase
+pub fn focus_timer(ends: Timestamp) -> impl Stream<Item = Message> {
+    iced::stream::channel(1, move |mut output| async move {
+        thread::spawn(move || {
+            let wait = Duration::try_from(ends.duration_since(Timestamp::now())).unwrap_or(Duration::ZERO);
+            thread::sleep(wait);
+            let _ = block_on(output.send(Message::FocusPhaseEnded));
+        });
+        std::future::pending::<()>().await
+    })
+}
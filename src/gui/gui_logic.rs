@@ -1,4 +1,4 @@
-use jiff::{Span, Zoned};
+use jiff::{Span, Timestamp, Zoned};
 use serde::{Deserialize, Serialize};
 use crate::gui::gui_main::Location;
 
@@ -8,7 +8,27 @@ pub struct WorkTimes {
     pub start: Option<Zoned>,
     pub end: Option<Zoned>,
     pub duration: Option<Span>,
-    pub pause: Option<Span>
+    pub pause: Option<Span>,
+    // every manual change of start or end, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub corrections: Vec<Correction>,
+    // what was done, e.g. `customer call`
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+    // a focus block that ran its full length, see `FocusSession`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub focus: bool,
+}
+
+// one manual change of an interval, kept for audits
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Correction {
+    pub corrected_at: Timestamp,
+    pub original_start: Option<Zoned>,
+    pub original_end: Option<Zoned>,
+    pub start: Option<Zoned>,
+    pub end: Option<Zoned>,
+    pub reason: String,
 }
 
 impl WorkTimes {
@@ -19,9 +39,16 @@ impl WorkTimes {
             end: None,
             duration: None,
             pause: None,
+            corrections: vec![],
+            note: String::new(),
+            focus: false,
         }
     }
 
+    pub fn is_corrected(&self) -> bool {
+        !self.corrections.is_empty()
+    }
+
     pub fn set_start(&mut self) {
         self.start = Some(Zoned::now());
     }
@@ -52,16 +79,31 @@ pub struct OneDaysWork {
     pub sum_pause: Option<Span>,
     pub vacation: bool,
     pub added_workday: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
 }
 
 impl OneDaysWork {
 
+    pub fn init(date: jiff::civil::Date) -> OneDaysWork {
+        OneDaysWork {
+            date,
+            location: Some(Location::Homeoffice),
+            work_duration: vec![],
+            sum_work: None,
+            sum_pause: None,
+            vacation: false,
+            added_workday: false,
+            note: String::new(),
+        }
+    }
+
     pub fn set_date(&mut self) {
         self.date = Zoned::now().date()
     }
 
     pub fn start(&mut self) {
-        if self.work_duration.len() == 0 {
+        if self.work_duration.is_empty() {
             self.set_date();
             let work_times = WorkTimes::build_new_work_times();
             self.work_duration.push(work_times);
@@ -101,6 +143,164 @@ impl OneDaysWork {
         }
     }
 
+    // adds a finished interval, e.g. from an import. returns false if it overlaps an existing interval,
+    // so the same time is never counted twice
+    pub fn add_interval(&mut self, work_times: WorkTimes, workday_span: Span) -> bool {
+        let (Some(start), Some(end)) = (&work_times.start, &work_times.end) else {
+            return false;
+        };
+        if self.overlaps(start, end) {
+            return false;
+        }
+        self.work_duration.push(work_times);
+        self.recompute(workday_span);
+        true
+    }
+
+    // whether the time from `start` to `end` overlaps one of the intervals, a running one lasts until now
+    pub fn overlaps(&self, start: &Zoned, end: &Zoned) -> bool {
+        self.work_duration.iter().any(|existing| {
+            let existing_end = existing.end.clone().unwrap_or(Zoned::now());
+            existing.start.as_ref().is_some_and(|existing_start| existing_start < end && *start < existing_end)
+        })
+    }
+
+    // changes start and end of an interval and records the correction, a reason is required
+    pub fn correct_interval(&mut self, index: usize, start: Zoned, end: Option<Zoned>, reason: &str, workday_span: Span) -> Result<(), String> {
+        if reason.trim().is_empty() {
+            return Err("a reason is required".to_owned());
+        }
+        if end.as_ref().is_some_and(|end| *end <= start) {
+            return Err("the end must be after the start".to_owned());
+        }
+        // only the last interval can still be running
+        if end.is_none() && index + 1 != self.work_duration.len() {
+            return Err("only the last interval can be left without an end".to_owned());
+        }
+        let overlaps = self.work_duration.iter().enumerate().any(|(i, other)| {
+            let other_end = other.end.clone().unwrap_or(Zoned::now());
+            i != index
+                && other.start.as_ref().is_some_and(|other_start| {
+                    *other_start < end.clone().unwrap_or(Zoned::now()) && start < other_end
+                })
+        });
+        if overlaps {
+            return Err("the interval overlaps another interval".to_owned());
+        }
+        let Some(work_times) = self.work_duration.get_mut(index) else {
+            return Err("no such interval".to_owned());
+        };
+        if work_times.start.as_ref() == Some(&start) && work_times.end == end {
+            return Ok(());
+        }
+
+        work_times.corrections.push(Correction {
+            corrected_at: Timestamp::now(),
+            original_start: work_times.start.clone(),
+            original_end: work_times.end.clone(),
+            start: Some(start.clone()),
+            end: end.clone(),
+            reason: reason.trim().to_owned(),
+        });
+        work_times.start = Some(start);
+        work_times.end = end;
+        work_times.duration = None;
+        self.recompute(workday_span);
+        Ok(())
+    }
+
+    // ends the running interval where the user went idle. with `resume` a new interval continues from
+    // there and the time away becomes the break between the two, without it tracking stops
+    pub fn split_running(&mut self, idle_from: Zoned, resume: Option<Zoned>, workday_span: Span) -> bool {
+        let Some(index) = self.work_duration.iter().position(|work_times| work_times.start.is_some() && work_times.end.is_none()) else {
+            return false;
+        };
+        let running = self.work_duration[index].clone();
+        // idle since before the interval started, nothing of it was work
+        if idle_from <= *running.start.as_ref().unwrap() {
+            self.work_duration.remove(index);
+        } else {
+            self.work_duration[index].end = Some(idle_from);
+        }
+        match resume {
+            Some(resume) => self.continue_from(&running, resume, workday_span),
+            None => self.recompute(workday_span),
+        }
+        true
+    }
+
+    // the interval still open, it is always the last one
+    pub fn running(&self) -> Option<&WorkTimes> {
+        self.work_duration.last().filter(|work_times| work_times.start.is_some() && work_times.end.is_none())
+    }
+
+    // tracking on this day ends at `end`, intervals going on then end there and later ones are dropped
+    pub fn end_at(&mut self, end: &Zoned, workday_span: Span) {
+        self.work_duration.retain(|work_times| work_times.start.as_ref().is_none_or(|start| start < end));
+        for work_times in &mut self.work_duration {
+            if work_times.end.as_ref().is_none_or(|interval_end| interval_end > end) {
+                work_times.end = Some(end.clone());
+            }
+        }
+        self.recompute(workday_span);
+    }
+
+    // starts an interval continuing `running`, with its label and note
+    pub fn continue_from(&mut self, running: &WorkTimes, start: Zoned, workday_span: Span) {
+        self.work_duration.push(WorkTimes {
+            label: running.label.clone(),
+            start: Some(start),
+            note: running.note.clone(),
+            ..WorkTimes::init()
+        });
+        self.recompute(workday_span);
+    }
+
+    pub fn focus_blocks(&self) -> usize {
+        self.work_duration.iter().filter(|work_times| work_times.focus).count()
+    }
+
+    // case insensitive search in the day's note, the intervals' notes and labels
+    pub fn matches_text(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        let contains = |text: &str| text.to_lowercase().contains(&query);
+        query.is_empty()
+            || contains(&self.note)
+            || self.work_duration.iter().any(|work_times| {
+                contains(&work_times.note) || work_times.label.as_deref().is_some_and(contains)
+            })
+    }
+
+    // sorts the intervals and recomputes durations, breaks and sums, vacation and generic workdays are credited again
+    pub fn recompute(&mut self, workday_span: Span) {
+        // the running interval stays last, starting, stopping and the tray look at the last interval
+        self.work_duration.sort_by_key(|work_times| (work_times.end.is_none(), work_times.start.as_ref().map(Zoned::timestamp)));
+
+        for i in 0..self.work_duration.len() {
+            let next_start = self.work_duration.get(i + 1).and_then(|next| next.start.clone());
+            let work_times = &mut self.work_duration[i];
+            if let (Some(start), Some(end)) = (&work_times.start, &work_times.end) {
+                work_times.duration = Some(start.until(end).unwrap());
+            }
+            work_times.pause = match (&work_times.end, next_start) {
+                (Some(end), Some(next_start)) if next_start >= *end => Some(end.until(&next_start).unwrap()),
+                _ => None,
+            };
+        }
+
+        self.sum_durations();
+        self.sum_pauses();
+
+        let mut sum_work = self.sum_work.unwrap();
+        if self.vacation {
+            sum_work = sum_work.checked_add(workday_span).unwrap();
+        }
+        if self.added_workday {
+            sum_work = sum_work.checked_add(workday_span).unwrap();
+        }
+        self.sum_work = Some(sum_work);
+    }
+
     pub fn sum_durations(&mut self) {
         let work_times_vec = &self.work_duration;
         let mut sum = Span::new();
@@ -1,15 +1,34 @@
 use std::collections::HashMap;
-use iced::Task;
+use std::path::PathBuf;
+use iced::{event, keyboard, window, Point, Size, Subscription, Task};
 use iced::{alignment, Element, Length, Padding};
-use iced::widget::{button, checkbox, column, container, horizontal_rule, horizontal_space, pick_list, row, text, vertical_space, Button, Column, Container, Row, Text};
+use iced::widget::{button, checkbox, column, container, horizontal_rule, horizontal_space, pick_list, row, scrollable, text, text_input, vertical_space, Button, Column, Container, Row, Text};
 use iced_aw::{date_picker, date_picker::Date};
 use jiff::{Span, SpanRound, Unit, Zoned};
 use serde::{Deserialize, Serialize};
 
 use crate::config::Config;
-use crate::utils::{compute_hours_and_minutes, compute_should_hours, format_duration, jiff_date_from_picker};
+use crate::i18n::{format_date, format_day, set_language, t, tf};
+use crate::utils::{compute_should_hours, format_contingent, format_duration, format_hours, format_time_of_day, jiff_date_from_picker};
 use crate::gui::gui_logic::{OneDaysWork};
-use crate::gui::serialize::{export, init_calendar, Calendar};
+use crate::gui::api::{api_stream, handle_request, ApiCall, ApiResponse};
+use crate::gui::crypto::{encrypt_existing_data, is_encrypted_file, needs_unlock, unlock};
+use crate::instance::control_stream;
+use crate::gui::import::{apply_import, import, ImportMode, ImportPreview};
+use crate::gui::corrections::{export_corrections, parse_corrected_time, parse_end_after};
+use crate::gui::search::{search, Absence, SearchForm};
+use crate::gui::idle::{idle_stream, IdlePeriod};
+use crate::gui::notify::{due_reminders, reminder_text, send_notification, tick_stream, worked_hours, SentReminders};
+use crate::gui::tray::{is_shown, tray_stream, update_tray, TrayStatus};
+use crate::gui::palette::{commands, filter, Palette, PALETTE_INPUT};
+use crate::gui::appearance::{full_size, system_theme_stream, window_geometry, ThemeChoice, COMPACT_WINDOW_SIZE};
+use crate::gui::focus::{focus_timer, FocusPhase, FocusSession};
+use crate::gui::recover::{find_dangling, RecoverForm, RECOVER_REASON};
+use crate::gui::session::{session_action, session_stream, stop_action, SessionAction, SessionEvent};
+use crate::gui::serialize::{data_path, export, sorted_work_days, init_calendar, read_calendar, try_read_calendar, Calendar};
+use crate::gui::journal::{journal_path, read_journal, rebuild, start_journal, Journal};
+use crate::gui::sync::{append_changes, merge_logs, read_logs, sync_dir, sync_stream, Change};
+use crate::gui::watch::{merge_calendars, same_calendar, watch_stream, ReloadConflict};
 
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -19,10 +38,18 @@ pub enum Location {
 }
 
 impl Location {
-    const ALL: [Location; 2] = [
+    pub(crate) const ALL: [Location; 2] = [
         Location::Homeoffice,
         Location::Office,
     ];
+
+    // the untranslated name, also accepted by the importers
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Location::Homeoffice => "home-office",
+            Location::Office => "office",
+        }
+    }
 }
 
 impl std::fmt::Display for Location {
@@ -30,10 +57,7 @@ impl std::fmt::Display for Location {
         write!(
             f,
             "{}",
-            match self {
-                Location::Homeoffice => "home-office",
-                Location::Office => "office",
-            }
+            t(self.name())
         )
     }
 }
@@ -45,11 +69,134 @@ pub struct App {
     pub show_picker: bool,
     pub date: jiff::civil::Date,
     pub calendar: HashMap<String, OneDaysWork>,
+    // an import waiting for the user to choose merge or replace
+    pub import_preview: Option<ImportPreview>,
+    // the calendar as last read from or written to the data file, the base for merging external changes
+    pub saved: HashMap<String, OneDaysWork>,
+    // the calendar as of the last journal entry, the next action is journaled as the difference to it
+    pub journaled: HashMap<String, OneDaysWork>,
+    // days edited here and in the file at the same time, shown until the user picks a side
+    pub reload_conflict: Option<ReloadConflict>,
+    // the data file, next to the binary, see `data_path`
+    #[serde(skip)]
+    pub data_path: PathBuf,
+    #[serde(skip)]
+    pub journal: Journal,
+    // the interval being corrected
+    pub correction: Option<CorrectionForm>,
+    // asks for the passphrase of an encrypted data file, the app is locked while it is set
+    pub unlock: Option<UnlockForm>,
+    // the search view is shown while it is set
+    pub search: Option<SearchForm>,
+    // the user came back after being idle with the timer running, asked what the time away was
+    pub idle_prompt: Option<IdlePeriod>,
+    // the running interval was ended by suspend, shutdown or lock, see `session_action`
+    pub auto_stopped: bool,
+    // intervals of past days that were never stopped, asked for one after the other at startup
+    pub recover: Option<RecoverForm>,
+    // the reminders already notified today
+    pub reminders: SentReminders,
+    // focus mode is on while it is set
+    pub focus: Option<FocusSession>,
+    // the command palette is shown while it is set
+    pub palette: Option<Palette>,
+    // the desktop prefers a dark theme, used when the config's theme follows the system
+    pub system_dark: bool,
+    // the day note while it is typed, saved on enter, when another day is opened or with the next action
+    pub note_draft: Option<String>,
+    // every label used so far for the tray menu, updated when the calendar is written
+    #[serde(skip)]
+    pub projects: Vec<String>,
+    // the day this process last saw tracking running, an interval left open by an earlier run isn't continued after midnight
+    #[serde(skip)]
+    pub running_on: Option<jiff::civil::Date>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct UnlockForm {
+    pub passphrase: String,
+    pub confirmation: String,
+    // encryption was just turned on, the passphrase is chosen instead of checked
+    pub new_passphrase: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CorrectionForm {
+    pub date: jiff::civil::Date,
+    pub index: usize,
+    pub start: String,
+    // empty while the interval is still running
+    pub end: String,
+    pub reason: String,
+    pub note: String,
+    // the times when the form was opened, a changed note alone needs no reason
+    pub original_start: String,
+    pub original_end: String,
+    pub error: Option<String>,
+}
+
+// shown until the passphrase is entered, nothing is read or written before that
+fn locked_app(config: Config) -> App {
+    let path = data_path();
+    let new_passphrase = !is_encrypted_file(&path);
+    App {
+        config,
+        state: State::Stopped,
+        show_picker: false,
+        date: Zoned::now().date(),
+        calendar: HashMap::new(),
+        import_preview: None,
+        saved: HashMap::new(),
+        journaled: HashMap::new(),
+        reload_conflict: None,
+        journal: Journal::new(journal_path(&path)),
+        data_path: path,
+        correction: None,
+        search: None,
+        idle_prompt: None,
+        auto_stopped: false,
+        recover: None,
+        reminders: SentReminders::default(),
+        focus: None,
+        palette: None,
+        system_dark: true,
+        note_draft: None,
+        projects: vec![],
+        running_on: None,
+        unlock: Some(UnlockForm {
+            new_passphrase,
+            ..Default::default()
+        }),
+    }
 }
 
 fn init_app_state() -> App {
     let config = Config::get_config();
-    let calendar = init_calendar();
+    let path = data_path();
+    let mut calendar = init_calendar(&path);
+    let mut saved = read_calendar(&path).map(Calendar::into_hashmap).unwrap_or_default();
+    if !path.exists() {
+        if let Ok(entries) = read_journal(&journal_path(&path)) {
+            println!("WARN ::: {} is missing, restoring it from the journal", path.display());
+            saved = rebuild(entries, config.get_workday_span());
+            calendar = saved.clone();
+            let today = Zoned::now().date();
+            calendar.entry(today.to_string()).or_insert_with(|| OneDaysWork::init(today));
+            Calendar::update(&saved, &path);
+        }
+    }
+    start_journal(&journal_path(&path), &saved);
+    if let Some(sync_dir) = config.sync_dir.as_ref().filter(|_| config.encrypt_data) {
+        println!("ERROR ::: not syncing with {}, the sync logs can't be encrypted. turn off encrypt_data or sync_dir", sync_dir.display());
+    }
+    if let Some(synced) = crate::gui::sync::load(&config, &saved) {
+        calendar = synced;
+        let today = Zoned::now().date();
+        calendar.entry(today.to_string()).or_insert_with(|| OneDaysWork::init(today));
+        Calendar::update(&calendar, &path);
+        saved = calendar.clone();
+    }
 
     let mut state = State::Stopped;
     if let Some(todays_work) = calendar.get(&Zoned::now().date().to_string()).unwrap().work_duration.last() {
@@ -58,12 +205,60 @@ fn init_app_state() -> App {
         }
     }
 
+    let entries = read_journal(&journal_path(&path)).unwrap_or_default();
+    // the last run ended with an automatic stop, e.g. the machine was shut down while tracking
+    let auto_stopped = entries.last().is_some_and(|entry| entry.action.starts_with("stop on"));
+    let activities: Vec<jiff::Timestamp> = entries.iter().map(|entry| entry.at).collect();
+    let dangling = find_dangling(&calendar, Zoned::now().date(), &activities, config.get_workday_span());
+    let projects = project_names(&calendar);
+
     App {
         config,
         state,
         date: Zoned::now().date(),
         show_picker: false,
         calendar,
+        import_preview: None,
+        journaled: saved.clone(),
+        saved,
+        reload_conflict: None,
+        journal: Journal::new(journal_path(&path)),
+        data_path: path,
+        correction: None,
+        unlock: None,
+        search: None,
+        idle_prompt: None,
+        auto_stopped,
+        recover: RecoverForm::new(dangling),
+        reminders: SentReminders::default(),
+        focus: None,
+        palette: None,
+        system_dark: true,
+        note_draft: None,
+        projects,
+        running_on: None,
+    }
+}
+
+// the labels of all intervals, an interval without one counts as `Work`
+fn project_names(calendar: &HashMap<String, OneDaysWork>) -> Vec<String> {
+    let mut projects: Vec<String> = calendar
+        .values()
+        .flat_map(|work_day| &work_day.work_duration)
+        .filter_map(|work_times| work_times.label.clone())
+        .chain(["Work".to_owned()])
+        .collect();
+    projects.sort();
+    projects.dedup();
+    projects
+}
+
+// tracking that was stopped by a shutdown goes on when the window opens again. only the gui
+// runs this, the command line builds the app too but must not start anything on its own
+fn resume_task(app: &App) -> Task<Message> {
+    match app.config.start_on_resume && app.auto_stopped {
+        true => Task::done(Message::Start),
+        false => Task::none(),
     }
 }
 
@@ -72,12 +267,80 @@ pub enum Message {
     Start,
     Stop,
     Export,
+    Import,
+    ApplyImport(ImportMode),
+    CancelImport,
     ChooseDate,
     SubmitDate(Date),
     CancelDate,
     LocationSelected(Location),
     VacationToggled(bool),
     AddFullWordDayToggled(bool),
+    Api(ApiCall),
+    Show,
+    DataFileChanged,
+    SyncLogsChanged,
+    PassphraseChanged(String),
+    PassphraseConfirmationChanged(String),
+    Unlock,
+    Undo,
+    Redo,
+    EditInterval(usize),
+    CorrectionStartChanged(String),
+    CorrectionEndChanged(String),
+    CorrectionReasonChanged(String),
+    CorrectionNoteChanged(String),
+    DayNoteChanged(String),
+    SaveDayNote,
+    SaveCorrection,
+    CancelCorrection,
+    ExportCorrections,
+    KeepLocalChanges,
+    UseFileChanges,
+    OpenSearch,
+    CloseSearch,
+    SearchTextChanged(String),
+    SearchProjectChanged(String),
+    SearchTagChanged(String),
+    SearchLocationSelected(Location),
+    SearchAbsenceSelected(Absence),
+    SearchFromChanged(String),
+    SearchToChanged(String),
+    ClearSearchFilters,
+    OpenDay(jiff::civil::Date),
+    IdleDetected(IdlePeriod),
+    SessionEvent(SessionEvent),
+    RecoverEndChanged(String),
+    CloseDangling,
+    DiscardDangling,
+    KeepIdleTime,
+    DiscardIdleTime,
+    IdleAsBreak,
+    Tick,
+    SwitchProject(String),
+    CloseRequested(window::Id),
+    Quit,
+    ThemeSelected(ThemeChoice),
+    SystemThemeChanged(bool),
+    WindowResized(Size),
+    WindowMoved(Point),
+    ToggleCompact,
+    StartFocus,
+    StopFocus,
+    FocusPhaseEnded,
+    ToggleStartStop,
+    PreviousDay,
+    NextDay,
+    Today,
+    ToggleVacation,
+    ToggleGenericWorkday,
+    OpenPalette,
+    ClosePalette,
+    PaletteQueryChanged(String),
+    SubmitPalette,
+    RunCommand(Box<Message>),
+    // a key press mapped by `shortcut`
+    Shortcut(Box<Message>),
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -90,66 +353,384 @@ pub enum State {
 impl App {
 
 	pub(crate) fn new() -> (Self, Task<Message>) {
-		(
-            init_app_state(),
-            Task::none()
-        )
+        let config = Config::get_config();
+        set_language(config.language);
+        if needs_unlock(&config, &data_path()) {
+            return (locked_app(config), Task::none());
+        }
+        let app = init_app_state();
+        let task = resume_task(&app);
+		(app, task)
 	}
 
     pub(crate) fn theme(&self) -> iced::Theme {
-        iced::Theme::TokyoNightStorm
+        self.config.theme.theme(self.system_dark)
 	}
 
+    pub(crate) fn subscription(&self) -> Subscription<Message> {
+        let api = match self.config.api_port {
+            Some(port) => Subscription::run_with_id(port, api_stream(port)),
+            None => Subscription::none(),
+        };
+        let sync = match sync_dir(&self.config) {
+            Some(sync_dir) => Subscription::run_with_id(sync_dir.clone(), sync_stream(sync_dir.clone())),
+            None => Subscription::none(),
+        };
+        // polled only while an interval runs and no answer is pending
+        let idle = match (self.config.idle_minutes, self.is_running(), &self.idle_prompt) {
+            (Some(minutes), true, None) => Subscription::run_with_id(("idle", minutes), idle_stream(minutes)),
+            _ => Subscription::none(),
+        };
+        let system_theme = match self.config.theme {
+            ThemeChoice::System => Subscription::run(system_theme_stream),
+            _ => Subscription::none(),
+        };
+        let focus = match &self.focus {
+            Some(session) if session.phase != FocusPhase::Waiting => Subscription::run_with_id(("focus", session.ends.timestamp().as_second()), focus_timer(session.ends.timestamp())),
+            _ => Subscription::none(),
+        };
+        Subscription::batch([
+            api,
+            focus,
+            sync,
+            idle,
+            Subscription::run(tick_stream),
+            Subscription::run(tray_stream),
+            system_theme,
+            window::close_requests().map(Message::CloseRequested),
+            event::listen_with(window_geometry),
+            Subscription::run(control_stream),
+            Subscription::run(watch_stream),
+            Subscription::run(session_stream),
+            keyboard::on_key_press(|key, modifiers| shortcut(key, modifiers).map(|message| Message::Shortcut(Box::new(message)))),
+        ])
+    }
+
     pub(crate) fn update(&mut self, message: Message) -> Task<Message> {
+        let task = match self.unlock.is_some() {
+            true => self.update_locked(message),
+            false => self.handle_message(message),
+        };
+        update_tray(self.tray_status());
+        task
+    }
+
+    fn handle_message(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::Start => {
-                self.calendar.get_mut(&Zoned::now().date().to_string()).unwrap().start();
-                self.state = State::Started;
-                Calendar::update(&self.calendar);
+                self.start_work();
             }
             Message::Stop => {
-                self.calendar.get_mut(&Zoned::now().date().to_string()).unwrap().stop();
-                self.state = State::Stopped;
-                Calendar::update(&self.calendar);
+                self.stop_work();
+            }
+            Message::StartFocus => {
+                self.start_focus();
+            }
+            Message::StopFocus => {
+                self.stop_work();
+                self.focus = None;
+            }
+            Message::FocusPhaseEnded => {
+                self.end_focus_phase();
+            }
+            Message::ToggleStartStop => match self.state {
+                State::Started => {
+                    self.stop_work();
+                }
+                State::Stopped => {
+                    self.start_work();
+                }
+                State::NotToday => {}
+            },
+            Message::PreviousDay => {
+                self.open_day(self.date.yesterday().unwrap());
+            }
+            Message::NextDay => {
+                self.open_day(self.date.tomorrow().unwrap());
+            }
+            Message::Today => {
+                self.open_day(Zoned::now().date());
+            }
+            Message::ToggleVacation => {
+                let vacation = self.calendar[&self.date.to_string()].vacation;
+                return self.handle_message(Message::VacationToggled(!vacation));
+            }
+            Message::ToggleGenericWorkday => {
+                let added_workday = self.calendar[&self.date.to_string()].added_workday;
+                return self.handle_message(Message::AddFullWordDayToggled(!added_workday));
+            }
+            Message::OpenPalette => {
+                self.palette = Some(Palette::default());
+                return text_input::focus(text_input::Id::new(PALETTE_INPUT));
+            }
+            Message::ClosePalette => {
+                self.palette = None;
+            }
+            Message::PaletteQueryChanged(query) => {
+                if let Some(palette) = &mut self.palette {
+                    palette.query = query;
+                }
+            }
+            Message::SubmitPalette => {
+                let query = self.palette.as_ref().map(|palette| palette.query.clone()).unwrap_or_default();
+                if let Some(command) = filter(commands(self), &query).into_iter().next() {
+                    self.palette = None;
+                    return self.handle_message(command.message);
+                }
+            }
+            Message::RunCommand(message) => {
+                self.palette = None;
+                return self.handle_message(*message);
+            }
+            // while a form or view is on top only escape works, closing it
+            Message::Shortcut(message) => match (self.has_overlay(), *message) {
+                (false, message) => return self.handle_message(message),
+                (true, Message::ClosePalette) => self.close_overlay(),
+                (true, _) => {}
+            },
+            Message::Api(call) => {
+                let response = handle_request(self, &call.request);
+                call.respond(response);
+            }
+            Message::Show => {
+                return show_window();
+            }
+            Message::CloseRequested(id) => {
+                self.save_day_note();
+                self.config.save_appearance();
+                return close_window(id);
+            }
+            Message::Quit => {
+                self.save_day_note();
+                self.config.save_appearance();
+                return iced::exit();
+            }
+            Message::ThemeSelected(choice) => {
+                self.config.theme = choice;
+                self.config.save_appearance();
+            }
+            Message::SystemThemeChanged(dark) => {
+                self.system_dark = dark;
+            }
+            Message::WindowResized(size) => {
+                // the mini mode has its own fixed size
+                if !self.config.compact {
+                    self.config.window_size = Some((size.width, size.height));
+                }
+            }
+            Message::WindowMoved(position) => {
+                self.config.window_position = Some((position.x, position.y));
+            }
+            Message::ToggleCompact => {
+                self.config.compact = !self.config.compact;
+                self.config.save_appearance();
+                let size = match self.config.compact {
+                    true => {
+                        // the mini mode always shows today
+                        self.open_day(Zoned::now().date());
+                        COMPACT_WINDOW_SIZE
+                    }
+                    false => full_size(&self.config),
+                };
+                return window::get_latest().and_then(move |id| window::resize(id, size));
+            }
+            Message::SwitchProject(project) => {
+                self.switch_project(&project);
+            }
+            Message::DataFileChanged => {
+                self.reload();
+            }
+            Message::SyncLogsChanged => {
+                self.apply_sync();
+            }
+            Message::EditInterval(index) => {
+                let work_times = &self.calendar[&self.date.to_string()].work_duration[index];
+                let time = |time: &Option<Zoned>| time.as_ref().map(|time| time.strftime("%H:%M").to_string()).unwrap_or_default();
+                self.correction = Some(CorrectionForm {
+                    date: self.date,
+                    index,
+                    start: time(&work_times.start),
+                    end: time(&work_times.end),
+                    reason: String::new(),
+                    note: work_times.note.clone(),
+                    original_start: time(&work_times.start),
+                    original_end: time(&work_times.end),
+                    error: None,
+                });
+            }
+            Message::CorrectionStartChanged(start) => {
+                if let Some(form) = &mut self.correction {
+                    form.start = start;
+                }
+            }
+            Message::CorrectionEndChanged(end) => {
+                if let Some(form) = &mut self.correction {
+                    form.end = end;
+                }
+            }
+            Message::CorrectionReasonChanged(reason) => {
+                if let Some(form) = &mut self.correction {
+                    form.reason = reason;
+                }
+            }
+            Message::CorrectionNoteChanged(note) => {
+                if let Some(form) = &mut self.correction {
+                    form.note = note;
+                }
+            }
+            Message::DayNoteChanged(note) => {
+                self.note_draft = Some(note);
+            }
+            Message::SaveDayNote => {
+                self.save_day_note();
+            }
+            Message::SaveCorrection => {
+                self.save_correction();
+            }
+            Message::CancelCorrection => {
+                self.correction = None;
+            }
+            Message::IdleDetected(period) => {
+                if self.is_running() {
+                    self.idle_prompt = Some(period);
+                    return show_window();
+                }
+            }
+            Message::SessionEvent(event) => {
+                self.handle_session_event(event);
+            }
+            Message::RecoverEndChanged(end) => {
+                if let Some(form) = &mut self.recover {
+                    form.end = end;
+                }
+            }
+            Message::CloseDangling => {
+                self.close_dangling();
+            }
+            Message::DiscardDangling => {
+                if let Some(form) = self.recover.take() {
+                    let interval = &form.intervals[0];
+                    if let Some(work_day) = self.calendar.get_mut(&interval.date.to_string()) {
+                        work_day.work_duration.retain(|work_times| work_times.start.as_ref() != Some(&interval.start));
+                        work_day.recompute(self.config.get_workday_span());
+                        self.save("discard dangling interval");
+                    }
+                    self.recover = form.next();
+                }
+            }
+            Message::KeepIdleTime => {
+                self.idle_prompt = None;
+            }
+            Message::DiscardIdleTime => {
+                self.resolve_idle(false);
+            }
+            Message::IdleAsBreak => {
+                self.resolve_idle(true);
+            }
+            Message::Tick => {
+                self.continue_after_midnight();
+                // the view followed today until midnight, so it keeps doing so
+                let today = Zoned::now().date();
+                if self.date != today && !matches!(self.state, State::NotToday) {
+                    self.open_day(today);
+                }
+                if self.config.notifications {
+                    self.send_reminders();
+                }
+                self.note_running();
+            }
+            Message::OpenSearch => {
+                self.search = Some(SearchForm::default());
+                self.run_search();
+            }
+            Message::CloseSearch => {
+                self.search = None;
+            }
+            Message::SearchTextChanged(input) => {
+                self.change_search(|form| form.query.text = input);
+            }
+            Message::SearchProjectChanged(input) => {
+                self.change_search(|form| form.query.project = input);
+            }
+            Message::SearchTagChanged(input) => {
+                self.change_search(|form| form.query.tag = input);
+            }
+            Message::SearchLocationSelected(location) => {
+                self.change_search(|form| form.query.location = Some(location));
+            }
+            Message::SearchAbsenceSelected(absence) => {
+                self.change_search(|form| form.query.absence = Some(absence));
+            }
+            Message::SearchFromChanged(input) => {
+                self.change_search(|form| form.from = input);
+            }
+            Message::SearchToChanged(input) => {
+                self.change_search(|form| form.to = input);
+            }
+            Message::ClearSearchFilters => {
+                self.change_search(|form| *form = SearchForm::default());
+            }
+            Message::OpenDay(date) => {
+                self.search = None;
+                self.open_day(date);
+            }
+            Message::ExportCorrections => {
+                export_corrections(&self.config, &sorted_work_days(&self.calendar), self.date);
+            }
+            Message::PassphraseChanged(_) | Message::PassphraseConfirmationChanged(_) | Message::Unlock => {}
+            Message::Undo => {
+                self.undo();
+            }
+            Message::Redo => {
+                self.redo();
+            }
+            Message::KeepLocalChanges => {
+                self.reload_conflict = None;
+                self.save("keep local changes");
+            }
+            Message::UseFileChanges => {
+                if let Some(conflict) = self.reload_conflict.take() {
+                    for date in conflict.dates {
+                        match conflict.remote.get(&date.to_string()) {
+                            Some(work_day) => self.calendar.insert(date.to_string(), work_day.clone()),
+                            None => self.calendar.remove(&date.to_string()),
+                        };
+                    }
+                    self.ensure_selected_days();
+                    self.refresh_state();
+                    self.save("use file changes");
+                }
             }
             Message::Export => {
-                export(&self.config);
+                export(&mut self.config, self.date);
+            }
+            Message::Import => {
+                self.import_preview = import(&self.calendar, &self.config);
+            }
+            Message::ApplyImport(mode) => {
+                if let Some(preview) = self.import_preview.take() {
+                    apply_import(&mut self.calendar, preview.data, mode, &self.config);
+                    self.calendar.entry(self.date.to_string()).or_insert_with(|| OneDaysWork::init(self.date));
+                    self.refresh_state();
+                    self.save("import");
+                }
+            }
+            Message::CancelImport => {
+                self.import_preview = None;
             }
             Message::ChooseDate => {
                 self.show_picker = true;
             }
             Message::SubmitDate(date) => {
-                let date = jiff_date_from_picker(date);
-                if let None = self.calendar.get(&date.to_string()) {
-                    self.calendar.insert(date.to_string(), OneDaysWork {
-                        date,
-                        location: Some(Location::Homeoffice),
-                        work_duration: vec![],
-                        sum_work: None,
-                        sum_pause: None,
-                        vacation: false,
-                        added_workday: false,
-                    });
-                    }
-                self.date = date;
                 self.show_picker = false;
-                if date != Zoned::now().date() {
-                    self.state = State::NotToday;
-                } else {
-                    self.state = State::Stopped;
-                    if let Some(todays_work) = self.calendar.get(&Zoned::now().date().to_string()).unwrap().work_duration.last() {
-                        if  todays_work.end.is_none() && todays_work.start.is_some()  {
-                            self.state = State::Started
-                        }
-                    }
-                }
+                self.open_day(jiff_date_from_picker(date));
             }
             Message::CancelDate => {
                 self.show_picker = false;
             }
             Message::LocationSelected(location) => {
                 self.calendar.get_mut(&self.date.to_string()).unwrap().location = Some(location);
-                Calendar::update(&self.calendar);
+                self.save("location");
             }
             Message::VacationToggled(is_vacation) => {
                 self.calendar.get_mut(&self.date.to_string()).unwrap().vacation = is_vacation;
@@ -169,7 +750,7 @@ impl App {
                         self.calendar.get_mut(&self.date.to_string()).unwrap().sum_work = Some(new_sum);
                     }
                 }
-                Calendar::update(&self.calendar);
+                self.save("vacation");
             },
             Message::AddFullWordDayToggled(is_added_workday) => {
                 self.calendar.get_mut(&self.date.to_string()).unwrap().added_workday = is_added_workday;
@@ -189,13 +770,34 @@ impl App {
                         self.calendar.get_mut(&self.date.to_string()).unwrap().sum_work = Some(new_sum);
                     }
                 }
-                Calendar::update(&self.calendar);
+                self.save("generic workday");
             }
         }
         Task::none()
     }
 
-	pub(crate) fn view(&self) -> Element<Message> {
+	pub(crate) fn view(&self) -> Element<'_, Message> {
+        if let Some(form) = &self.unlock {
+            return unlock_view(form);
+        }
+        if let Some(form) = &self.recover {
+            return recover_form(form);
+        }
+        if let Some(preview) = &self.import_preview {
+            return import_preview(preview);
+        }
+        if let Some(form) = &self.correction {
+            return correction_form(form);
+        }
+        if let Some(palette) = &self.palette {
+            return palette_view(self, palette);
+        }
+        if let Some(form) = &self.search {
+            return search_view(form);
+        }
+        if self.config.compact {
+            return compact_view(self);
+        }
 
         let pick_list = row!(pick_list(
             &Location::ALL[..],
@@ -205,33 +807,44 @@ impl App {
             .width(Length::Fill)
             .padding(Padding{top:5., right:0., bottom:5., left:10.});
 
-        let vacation_checkbox = row!(checkbox("Vacation", self.calendar.get(&self.date.to_string()).unwrap().vacation)
+        let vacation_checkbox = row!(checkbox(t("Vacation"), self.calendar.get(&self.date.to_string()).unwrap().vacation)
             .on_toggle(Message::VacationToggled))
             .padding(Padding{top:5., right:0., bottom:2., left:10.});
 
-        let add_full_work_day_checkbox = row!(checkbox("Generic Workday", self.calendar.get(&self.date.to_string()).unwrap().added_workday)
+        let add_full_work_day_checkbox = row!(checkbox(t("Generic Workday"), self.calendar.get(&self.date.to_string()).unwrap().added_workday)
             .on_toggle(Message::AddFullWordDayToggled))
             .padding(Padding{top:5., right:0., bottom:25., left:10.});
 
         let main_container = Container::new(
+            column!(
+            reload_conflict_banner(self.reload_conflict.as_ref()),
+            idle_banner(self.idle_prompt.as_ref()),
+            focus_banner(self.focus.as_ref()),
             row!(
                 column!(
                     date_section(self),
-                    one_days_work(&self.calendar.get(&self.date.to_string()).unwrap()),
+                    one_days_work(self.calendar.get(&self.date.to_string()).unwrap()),
+                    vertical_space(),
+                    text_input(t("note for this day"), self.note_draft.as_deref().unwrap_or(&self.calendar.get(&self.date.to_string()).unwrap().note))
+                        .on_input(Message::DayNoteChanged)
+                        .on_submit(Message::SaveDayNote),
                 )
                 .padding(Padding::from(10))
                 .height(Length::Fill)
                 .width(Length::FillPortion(4)),
                 column!(
                     start_stop_btn(&self.state),
+                    focus_btn(&self.state, self.focus.as_ref()),
                     pick_list,
                     vacation_checkbox,
                     add_full_work_day_checkbox,
                     table_totals(self),
                     vertical_space(),
                     row!(
+                        button(t("mini")).on_press(Message::ToggleCompact),
                         horizontal_space(),
-                        button("export").on_press(Message::Export),
+                        button(t("import")).on_press(Message::Import),
+                        button(t("export")).on_press(Message::Export),
                     )
                     .width(Length::Fill)
                     .spacing(15)
@@ -240,6 +853,7 @@ impl App {
                 .height(Length::Fill)
                 .width(Length::FillPortion(2)),
             )
+            )
         );
 
         main_container.height(Length::Fill)
@@ -249,7 +863,516 @@ impl App {
         .into()
     }
 
-    fn total_worked_hours(&self) -> f32 {
+    // journals what the action changed since the last write, then writes the calendar
+    pub(crate) fn save(&mut self, action: &str) {
+        // a note typed before is its own action, so undoing this one keeps it
+        self.save_day_note();
+        let changes = self.journal.record(action, &self.journaled, &self.calendar);
+        self.journaled = self.calendar.clone();
+        self.log_sync(changes);
+        self.write();
+        self.note_running();
+    }
+
+    fn note_running(&mut self) {
+        self.running_on = self.is_running().then(|| Zoned::now().date());
+    }
+
+    // in the order `view` shows them
+    fn has_overlay(&self) -> bool {
+        self.recover.is_some() || self.import_preview.is_some() || self.correction.is_some() || self.palette.is_some() || self.search.is_some()
+    }
+
+    // the intervals left running have to be answered, the other overlays can be closed
+    fn close_overlay(&mut self) {
+        if self.recover.is_some() || self.import_preview.take().is_some() || self.correction.take().is_some() || self.palette.take().is_some() {
+            return;
+        }
+        self.search = None;
+    }
+
+    // the day note is journaled once when typing is done, not for every key
+    pub(crate) fn save_day_note(&mut self) {
+        let Some(note) = self.note_draft.take() else {
+            return;
+        };
+        let work_day = self.calendar.entry(self.date.to_string()).or_insert_with(|| OneDaysWork::init(self.date));
+        if work_day.note != note {
+            work_day.note = note;
+            self.save("note");
+        }
+    }
+
+    // writes the calendar and remembers what the file now contains. while the reload conflict is open
+    // the file keeps its version until the user decided, the changes stay in memory and the journal
+    fn write(&mut self) {
+        self.projects = project_names(&self.calendar);
+        if self.reload_conflict.is_some() {
+            return;
+        }
+        Calendar::update(&self.calendar, &self.data_path);
+        self.saved = self.calendar.clone();
+    }
+
+    // appends changes to this device's sync log
+    fn log_sync(&self, changes: Vec<Change>) {
+        if let Err(error) = append_changes(&self.config, changes) {
+            println!("ERROR ::: could not write sync log: {error}");
+        }
+    }
+
+    fn update_locked(&mut self, message: Message) -> Task<Message> {
+        let Some(form) = &mut self.unlock else {
+            return Task::none();
+        };
+        match message {
+            Message::PassphraseChanged(passphrase) => form.passphrase = passphrase,
+            Message::PassphraseConfirmationChanged(confirmation) => form.confirmation = confirmation,
+            Message::Unlock => {
+                let unlocked = match form.new_passphrase {
+                    true if form.passphrase != form.confirmation => Err(t("the passphrases differ").to_owned()),
+                    true => encrypt_existing_data(&form.passphrase, &self.data_path),
+                    false => unlock(&form.passphrase, &self.data_path),
+                };
+                match unlocked {
+                    // the system theme is only sent again when it changes
+                    Ok(()) => {
+                        *self = App { system_dark: self.system_dark, ..init_app_state() };
+                        return resume_task(self);
+                    }
+                    Err(error) => form.error = Some(error),
+                }
+            }
+            Message::Api(call) => call.respond(ApiResponse::error(423, "the data file is locked")),
+            Message::Show => return show_window(),
+            Message::CloseRequested(id) => return close_window(id),
+            Message::Quit => return iced::exit(),
+            Message::SystemThemeChanged(dark) => self.system_dark = dark,
+            _ => {}
+        }
+        Task::none()
+    }
+
+    // closes the first dangling interval at the entered time, recorded as a correction
+    pub(crate) fn close_dangling(&mut self) {
+        let workday_span = self.config.get_workday_span();
+        let Some(form) = &mut self.recover else {
+            return;
+        };
+        let interval = form.intervals[0].clone();
+        let Some(work_day) = self.calendar.get_mut(&interval.date.to_string()) else {
+            return;
+        };
+        let index = work_day
+            .work_duration
+            .iter()
+            .position(|work_times| work_times.start.as_ref() == Some(&interval.start))
+            .unwrap_or(interval.index);
+        let result = parse_end_after(&form.end, &interval.start)
+            .and_then(|end| work_day.correct_interval(index, interval.start.clone(), Some(end), RECOVER_REASON, workday_span));
+        match result {
+            Ok(()) => {
+                self.recover = self.recover.take().and_then(RecoverForm::next);
+                self.save("close dangling interval");
+            }
+            Err(error) => form.error = Some(error),
+        }
+    }
+
+    pub(crate) fn handle_session_event(&mut self, event: SessionEvent) {
+        match session_action(&self.config, event, self.is_running(), self.auto_stopped) {
+            SessionAction::Stop => {
+                self.calendar.get_mut(&Zoned::now().date().to_string()).unwrap().stop();
+                self.refresh_state();
+                self.save(stop_action(event));
+                self.auto_stopped = true;
+            }
+            SessionAction::Start => {
+                self.start_work();
+            }
+            SessionAction::Nothing => {}
+        }
+    }
+
+    // shows the day, days without entry get an empty one
+    pub(crate) fn open_day(&mut self, date: jiff::civil::Date) {
+        self.save_day_note();
+        self.calendar.entry(date.to_string()).or_insert_with(|| OneDaysWork::init(date));
+        self.date = date;
+        self.refresh_state();
+    }
+
+    // starts an interval that becomes a focus block when it runs for `focus_minutes`
+    pub(crate) fn start_focus(&mut self) {
+        if self.is_running() {
+            return;
+        }
+        let now = Zoned::now();
+        let today = now.date();
+        self.calendar.entry(today.to_string()).or_insert_with(|| OneDaysWork::init(today)).start();
+        self.auto_stopped = false;
+        self.refresh_state();
+        self.save("start focus");
+        self.focus = Some(FocusSession::focus(&self.config, &now));
+    }
+
+    // a focus block ends in a break, a break in the next focus block. `start` records the break as pause
+    pub(crate) fn end_focus_phase(&mut self) {
+        let Some(session) = self.focus.take() else {
+            return;
+        };
+        match session.phase {
+            // stopped by hand in the meantime, the interval doesn't count
+            FocusPhase::Focus if !self.is_running() => {}
+            FocusPhase::Focus => {
+                let work_day = self.calendar.get_mut(&Zoned::now().date().to_string()).unwrap();
+                work_day.stop();
+                work_day.work_duration.last_mut().unwrap().focus = true;
+                self.refresh_state();
+                self.save("focus block");
+                self.focus = Some(FocusSession::pause(&self.config, &Zoned::now()));
+                self.notify(t("Focus block done"), &tf("Take a {minutes} minute break.", &[("minutes", &self.config.focus_break_minutes)]));
+            }
+            // tracking never starts without the user, the next block waits for them
+            FocusPhase::Break | FocusPhase::Waiting => {
+                self.focus = Some(FocusSession { phase: FocusPhase::Waiting, ..session });
+                if session.phase == FocusPhase::Break {
+                    self.notify(t("Break is over"), &tf("Start the next {minutes} minute focus block when you're back.", &[("minutes", &self.config.focus_minutes)]));
+                }
+            }
+        }
+    }
+
+    fn notify(&self, summary: &str, body: &str) {
+        if self.config.notifications {
+            send_notification(summary.to_owned(), body.to_owned());
+        }
+    }
+
+    // notifies each reminder due for today once
+    pub(crate) fn send_reminders(&mut self) {
+        let now = Zoned::now();
+        let due = due_reminders(&self.config, self.calendar.get(&now.date().to_string()), &now);
+        for reminder in self.reminders.take_new(now.date(), due) {
+            let (summary, body) = reminder_text(reminder, &self.config);
+            send_notification(summary, body);
+        }
+    }
+
+    // splits the running interval at the start of the idle period, as a break tracking continues.
+    // the time away can reach past midnight, the interval was then continued in the new day. everything
+    // tracked after the user left goes and tracking goes on in the day the user came back
+    pub(crate) fn resolve_idle(&mut self, as_break: bool) {
+        let Some(period) = self.idle_prompt.take() else {
+            return;
+        };
+        let workday_span = self.config.get_workday_span();
+        let time_zone = jiff::tz::TimeZone::system();
+        let (from, to) = (period.from.to_zoned(time_zone.clone()), period.to.to_zoned(time_zone));
+        let today = Zoned::now().date().max(to.date());
+        let days: Vec<jiff::civil::Date> = std::iter::successors(Some(from.date()), |date| date.tomorrow().ok())
+            .take_while(|date| *date <= today)
+            .collect();
+        let Some((running_date, running)) = days.iter().rev().find_map(|date| {
+            let running = self.calendar.get(&date.to_string())?.running()?;
+            Some((*date, running.clone()))
+        }) else {
+            return;
+        };
+
+        for date in &days {
+            let Some(work_day) = self.calendar.get_mut(&date.to_string()) else {
+                continue;
+            };
+            match *date == running_date {
+                true => {
+                    work_day.split_running(from.clone(), (as_break && *date == to.date()).then(|| to.clone()), workday_span);
+                }
+                false => work_day.end_at(&from, workday_span),
+            }
+        }
+        if as_break && running_date != to.date() {
+            let date = to.date();
+            self.calendar
+                .entry(date.to_string())
+                .or_insert_with(|| OneDaysWork::init(date))
+                .continue_from(&running, to, workday_span);
+        }
+        self.refresh_state();
+        self.save(if as_break { "idle as break" } else { "discard idle time" });
+    }
+
+    // an interval running at midnight ends with its day and goes on in the new one, so every day
+    // has its own hours and the interval can be stopped from today
+    pub(crate) fn continue_after_midnight(&mut self) {
+        let today = Zoned::now().date();
+        let Ok(yesterday) = today.yesterday() else {
+            return;
+        };
+        // left open by a run that ended before midnight, the recover form asks how it ended
+        if self.recover.is_some() || self.running_on != Some(yesterday) {
+            return;
+        }
+        let workday_span = self.config.get_workday_span();
+        let Some(work_day) = self.calendar.get_mut(&yesterday.to_string()) else {
+            return;
+        };
+        let Some(running) = work_day.running().cloned() else {
+            return;
+        };
+        let Ok(midnight) = today.to_zoned(running.start.as_ref().unwrap().time_zone().clone()) else {
+            return;
+        };
+        work_day.end_at(&midnight, workday_span);
+        self.calendar
+            .entry(today.to_string())
+            .or_insert_with(|| OneDaysWork::init(today))
+            .continue_from(&running, midnight, workday_span);
+        self.refresh_state();
+        self.save("continue after midnight");
+    }
+
+    fn change_search(&mut self, change: impl FnOnce(&mut SearchForm)) {
+        if let Some(form) = &mut self.search {
+            change(form);
+        }
+        self.run_search();
+    }
+
+    // searches again with the current filters, the last result stays visible while a date is invalid
+    pub(crate) fn run_search(&mut self) {
+        let work_days = sorted_work_days(&self.calendar);
+        let Some(form) = &mut self.search else {
+            return;
+        };
+        match form.parse_dates() {
+            Ok(()) => {
+                form.result = search(&work_days, &form.query);
+                form.error = None;
+            }
+            Err(error) => form.error = Some(error),
+        }
+    }
+
+    // applies the correction form, the form stays open with an error if the input is invalid
+    pub(crate) fn save_correction(&mut self) {
+        let Some(form) = &mut self.correction else {
+            return;
+        };
+        let workday_span = self.config.get_workday_span();
+        let Some(work_day) = self.calendar.get_mut(&form.date.to_string()) else {
+            return;
+        };
+        let Some(work_times) = work_day.work_duration.get_mut(form.index) else {
+            return;
+        };
+        let like = match &work_times.start {
+            Some(start) => start.clone(),
+            None => form.date.to_zoned(jiff::tz::TimeZone::system()).unwrap(),
+        };
+        // the note is set before the times are corrected, correcting may reorder the intervals
+        let previous_note = std::mem::replace(&mut work_times.note, form.note.trim().to_owned());
+        let times_changed = form.start.trim() != form.original_start || form.end.trim() != form.original_end;
+        let result = match times_changed {
+            false => Ok(()),
+            true => parse_corrected_time(&form.start, &like).and_then(|start| {
+                let end = match form.end.trim().is_empty() {
+                    true => None,
+                    false => Some(parse_corrected_time(&form.end, &like)?),
+                };
+                work_day.correct_interval(form.index, start, end, &form.reason, workday_span)
+            }),
+        };
+        match result {
+            Ok(()) => {
+                let action = if times_changed { "correction" } else { "note" };
+                self.correction = None;
+                self.refresh_state();
+                self.save(action);
+            }
+            Err(error) => {
+                work_day.work_duration[form.index].note = previous_note;
+                form.error = Some(error);
+            }
+        }
+    }
+
+    pub(crate) fn undo(&mut self) {
+        if let Some(changes) = self.journal.undo(&mut self.calendar, self.config.get_workday_span()) {
+            self.after_replay(changes);
+        }
+    }
+
+    pub(crate) fn redo(&mut self) {
+        if let Some(changes) = self.journal.redo(&mut self.calendar, self.config.get_workday_span()) {
+            self.after_replay(changes);
+        }
+    }
+
+    fn after_replay(&mut self, changes: Vec<Change>) {
+        self.journaled = self.calendar.clone();
+        self.log_sync(changes);
+        self.ensure_selected_days();
+        self.refresh_state();
+        self.write();
+    }
+
+    // rebuilds the calendar from the change logs of all devices
+    pub(crate) fn apply_sync(&mut self) {
+        let Some(sync_dir) = sync_dir(&self.config) else {
+            return;
+        };
+        let mut synced = merge_logs(read_logs(sync_dir), self.config.get_workday_span());
+        for (key, work_day) in &self.calendar {
+            // days only opened in the app aren't logged yet
+            if work_day.work_duration.is_empty() && !synced.contains_key(key) {
+                synced.insert(key.clone(), work_day.clone());
+            }
+        }
+        if same_calendar(&synced, &self.calendar) {
+            return;
+        }
+        let before = std::mem::replace(&mut self.calendar, synced);
+        self.journal.record_foreign("sync", &before, &self.calendar);
+        self.journaled = self.calendar.clone();
+        self.ensure_selected_days();
+        self.refresh_state();
+        self.write();
+    }
+
+    // merges an external change of the data file, see `merge_calendars`
+    pub(crate) fn reload(&mut self) {
+        let remote = match try_read_calendar(&self.data_path) {
+            Ok(calendar) => calendar.into_hashmap(),
+            Err(error) => {
+                // most likely still being written, the next change triggers another reload
+                println!("WARN ::: could not reload {}: {error}", self.data_path.display());
+                return;
+            }
+        };
+        if same_calendar(&remote, &self.saved) {
+            return;
+        }
+
+        let merged = merge_calendars(&self.saved, &self.calendar, &remote);
+        let before = std::mem::replace(&mut self.calendar, merged.calendar);
+        // the external edit reaches the other devices through our log
+        let changes = self.journal.record_foreign("external change", &before, &self.calendar);
+        self.journaled = self.calendar.clone();
+        self.log_sync(changes);
+        self.saved = remote;
+        self.ensure_selected_days();
+        self.refresh_state();
+
+        // a conflict that is still open stays open, with the latest file content to choose from
+        let mut conflicts = merged.conflicts;
+        if let Some(pending) = self.reload_conflict.take() {
+            conflicts.extend(pending.dates);
+            conflicts.sort();
+            conflicts.dedup();
+        }
+        if conflicts.is_empty() {
+            // local changes that weren't in the file yet are written back
+            if !same_calendar(&self.calendar, &self.saved) {
+                self.write();
+            }
+        } else {
+            println!("WARN ::: data file changed on disk, conflicting edits on {:?}", conflicts);
+            self.reload_conflict = Some(ReloadConflict {
+                dates: conflicts,
+                remote: self.saved.clone(),
+            });
+        }
+    }
+
+    // the view expects entries for today and the selected date
+    fn ensure_selected_days(&mut self) {
+        for date in [Zoned::now().date(), self.date] {
+            self.calendar.entry(date.to_string()).or_insert_with(|| OneDaysWork::init(date));
+        }
+    }
+
+    pub(crate) fn is_running(&self) -> bool {
+        match self.calendar.get(&Zoned::now().date().to_string()).and_then(|work_day| work_day.work_duration.last()) {
+            Some(work_times) => work_times.end.is_none() && work_times.start.is_some(),
+            None => false,
+        }
+    }
+
+    // starts a new interval today, returns false if one is already running
+    pub(crate) fn start_work(&mut self) -> bool {
+        if self.is_running() {
+            return false;
+        }
+        let today = Zoned::now().date();
+        self.calendar.entry(today.to_string()).or_insert_with(|| OneDaysWork::init(today)).start();
+        self.auto_stopped = false;
+        self.focus = None;
+        self.refresh_state();
+        self.save("start");
+        true
+    }
+
+    // ends the running interval and starts one labelled `project`, or just starts it if nothing is running
+    pub(crate) fn switch_project(&mut self, project: &str) {
+        let today = Zoned::now().date();
+        let running = self.is_running();
+        let work_day = self.calendar.entry(today.to_string()).or_insert_with(|| OneDaysWork::init(today));
+        if running {
+            if work_day.work_duration.last().unwrap().label.as_deref().unwrap_or("Work") == project {
+                return;
+            }
+            work_day.stop();
+        }
+        work_day.start();
+        work_day.work_duration.last_mut().unwrap().set_label(project);
+        self.auto_stopped = false;
+        self.refresh_state();
+        self.save("switch project");
+    }
+
+    // what the tray icon shows
+    pub(crate) fn tray_status(&self) -> TrayStatus {
+        let now = Zoned::now();
+        let today = self.calendar.get(&now.date().to_string());
+        let running = self.is_running();
+        TrayStatus {
+            running,
+            today_hours: today.map(|work_day| worked_hours(work_day, &now)).unwrap_or(0.),
+            project: today
+                .and_then(|work_day| work_day.work_duration.last())
+                .filter(|_| running)
+                .map(|work_times| work_times.label.clone().unwrap_or("Work".to_owned())),
+            projects: self.projects.clone(),
+        }
+    }
+
+    // ends the running interval, returns false if nothing is running
+    pub(crate) fn stop_work(&mut self) -> bool {
+        if !self.is_running() {
+            return false;
+        }
+        self.calendar.get_mut(&Zoned::now().date().to_string()).unwrap().stop();
+        self.focus = None;
+        self.refresh_state();
+        self.save("stop");
+        true
+    }
+
+    // Started if today's last interval is still open, NotToday for any other selected date
+    pub(crate) fn refresh_state(&mut self) {
+        let today = Zoned::now().date();
+        if self.date != today {
+            self.state = State::NotToday;
+            return;
+        }
+        self.state = match self.is_running() {
+            true => State::Started,
+            false => State::Stopped,
+        };
+    }
+
+    pub(crate) fn total_worked_hours(&self) -> f32 {
         let mut sum: f32 = 0.;
         for work_day in self.calendar.values() {
             if let Some(work_hours) = work_day.sum_work {
@@ -263,9 +1386,56 @@ impl App {
 }
 
 
-fn start_stop_btn(state: &State) -> Element<Message> {
-    let start_btn = button("Start");
-    let stop_btn= button("Stop");
+// brings the window back, also when it was hidden to the tray
+fn show_window() -> Task<Message> {
+    window::get_latest().and_then(|id| Task::batch([window::change_mode(id, window::Mode::Windowed), window::gain_focus(id)]))
+}
+
+// with a tray icon the app keeps running, it is opened again from the tray menu
+fn close_window(id: window::Id) -> Task<Message> {
+    match is_shown() {
+        true => window::change_mode(id, window::Mode::Hidden),
+        false => iced::exit(),
+    }
+}
+
+// Ctrl+Z undoes the last action, Ctrl+Shift+Z redoes it
+// only called for keys no widget used, typing into a text input triggers nothing
+pub(crate) fn shortcut(key: keyboard::Key, modifiers: keyboard::Modifiers) -> Option<Message> {
+    use keyboard::key::Named;
+    use keyboard::Key;
+
+    if modifiers.command() {
+        return match key.as_ref() {
+            Key::Character(c) if c.eq_ignore_ascii_case("z") => match modifiers.shift() {
+                true => Some(Message::Redo),
+                false => Some(Message::Undo),
+            },
+            Key::Character(c) if c.eq_ignore_ascii_case("k") => Some(Message::OpenPalette),
+            Key::Character(c) if modifiers.shift() && c.eq_ignore_ascii_case("p") => Some(Message::OpenPalette),
+            _ => None,
+        };
+    }
+    if modifiers.alt() {
+        return None;
+    }
+    match key.as_ref() {
+        Key::Named(Named::Space) => Some(Message::ToggleStartStop),
+        Key::Named(Named::ArrowLeft) => Some(Message::PreviousDay),
+        Key::Named(Named::ArrowRight) => Some(Message::NextDay),
+        Key::Named(Named::Escape) => Some(Message::ClosePalette),
+        Key::Character(c) if c.eq_ignore_ascii_case("t") => Some(Message::Today),
+        Key::Character(c) if c.eq_ignore_ascii_case("e") => Some(Message::Export),
+        Key::Character(c) if c.eq_ignore_ascii_case("v") => Some(Message::ToggleVacation),
+        Key::Character(c) if c.eq_ignore_ascii_case("m") => Some(Message::ToggleCompact),
+        _ => None,
+    }
+}
+
+
+fn start_stop_btn(state: &State) -> Element<'_, Message> {
+    let start_btn = button(t("Start"));
+    let stop_btn= button(t("Stop"));
     let (start_btn, stop_btn) = match state {
         State::Stopped => (start_btn.on_press(Message::Start), stop_btn),
         State::Started => (start_btn, stop_btn.on_press(Message::Stop)),
@@ -283,16 +1453,45 @@ fn start_stop_btn(state: &State) -> Element<Message> {
 }
 
 
-fn date_section(app: &App) -> Element<Message> {
+// the mini mode, today's worked time including the running interval and the buttons
+fn compact_view(app: &App) -> Element<'_, Message> {
+    let now = Zoned::now();
+    let worked = app.calendar.get(&now.date().to_string()).map(|work_day| worked_hours(work_day, &now)).unwrap_or(0.);
+    row!(
+        text(format_hours(worked)).size(22),
+        start_stop_btn(&app.state),
+        button(t("full")).on_press(Message::ToggleCompact),
+    )
+    .align_y(alignment::Vertical::Center)
+    .padding(Padding{top: 0., right: 10., bottom: 0., left: 10.})
+    .height(Length::Fill)
+    .into()
+}
+
+
+fn focus_btn(state: &State, focus: Option<&FocusSession>) -> Element<'static, Message> {
+    let focus_btn = button(t("Focus"));
+    let waiting = focus.is_none_or(|session| session.phase == FocusPhase::Waiting);
+    let focus_btn = match (state, waiting) {
+        (State::Stopped, true) => focus_btn.on_press(Message::StartFocus),
+        _ => focus_btn,
+    };
+    row!(focus_btn)
+        .padding(Padding{top: 0., right: 0., bottom:0., left:10.})
+        .into()
+}
+
+
+fn date_section(app: &App) -> Element<'_, Message> {
     let mut picker_date = Date::today();
 
-    let date_label = app.date.to_string();
+    let date_label = format_day(app.date);
     picker_date.year = app.date.year() as i32;
     picker_date.month = app.date.month() as u32;
     picker_date.day = app.date.day() as u32;
 
 
-    let date_btn = Button::new(Text::new("Date"))
+    let date_btn = Button::new(Text::new(t("Date")))
         .on_press(Message::ChooseDate)
         .padding(Padding{top: 5., right: 5., bottom:5., left:5.});
 
@@ -304,26 +1503,48 @@ fn date_section(app: &App) -> Element<Message> {
         Message::SubmitDate,
     ).font_size(12);
 
+    let nav_padding = Padding{top: 5., right: 8., bottom:5., left:8.};
+    let today_btn = button(t("today")).padding(Padding{top: 5., right: 5., bottom:5., left:5.});
+    let today_btn = match app.date == Zoned::now().date() {
+        true => today_btn,
+        false => today_btn.on_press(Message::Today),
+    };
+
     row!(
         date_picker,
         container(
-            text(date_label)
-        ).padding(Padding{top: 3., right: 5., bottom:0., left:15.})
+            row!(
+                button("<").on_press(Message::PreviousDay).padding(nav_padding),
+                text(date_label),
+                button(">").on_press(Message::NextDay).padding(nav_padding),
+                today_btn,
+            )
+            .spacing(5)
+            .align_y(alignment::Vertical::Center)
+        ).padding(Padding{top: 0., right: 5., bottom:0., left:10.}),
+        horizontal_space(),
+        button(t("search"))
+            .on_press(Message::OpenSearch)
+            .padding(Padding{top: 5., right: 5., bottom:5., left:5.}),
+        button(t("corrections"))
+            .on_press(Message::ExportCorrections)
+            .padding(Padding{top: 5., right: 5., bottom:5., left:5.}),
     ).padding(Padding{top: 0., right: 0., bottom:15., left:0.})
     .into()
 }
 
 
-fn one_days_work(one_days_work: &OneDaysWork) -> Element<Message> {
+fn one_days_work(one_days_work: &OneDaysWork) -> Element<'_, Message> {
     let padding = Padding{top: 2., left: 5., bottom: 2., right: 0.};
     let col_width = 75;
 
-    let mut start_col: Column<Message> = column!( row!( text("Start") ) ).padding(padding).width(col_width);
-    let mut stop_col: Column<Message> = column!( row!( text("Stop") ) ).padding(padding).width(col_width);
-    let mut duration_col: Column<Message> = column!( row!( text("Duration") ) ).padding(padding).width(col_width);
-    let mut pause_col: Column<Message> = column!( row!( text("Break") ) ).padding(padding).width(col_width);
+    let mut start_col: Column<Message> = column!( row!( text(t("Start")) ) ).padding(padding).width(col_width);
+    let mut stop_col: Column<Message> = column!( row!( text(t("Stop")) ) ).padding(padding).width(col_width);
+    let mut duration_col: Column<Message> = column!( row!( text(t("Duration")) ) ).padding(padding).width(col_width);
+    let mut pause_col: Column<Message> = column!( row!( text(t("Break")) ) ).padding(padding).width(col_width);
+    let mut note_col: Column<Message> = column!( row!( text(t("Note")) ) ).padding(padding);
 
-    for item in &one_days_work.work_duration {
+    for (index, item) in one_days_work.work_duration.iter().enumerate() {
         let mut start_label = "".to_owned();
         let mut stop_label = "".to_owned();
         let mut duration_label = "".to_owned();
@@ -341,11 +1562,20 @@ fn one_days_work(one_days_work: &OneDaysWork) -> Element<Message> {
         if let Some(pause) = &item.pause {
             pause_label = format_duration(pause);
         }
+        if item.is_corrected() {
+            start_label += " *";
+        }
 
-        start_col = start_col.push(row!(text(start_label)));
+        // clicking the start time opens the correction form
+        let start_btn = button(text(start_label))
+            .on_press(Message::EditInterval(index))
+            .style(button::text)
+            .padding(0);
+        start_col = start_col.push(row!(start_btn));
         stop_col = stop_col.push(row!(text(stop_label)));
         duration_col = duration_col.push(row!(text(duration_label)));
         pause_col = pause_col.push(row!(text(pause_label)));
+        note_col = note_col.push(row!(text(&item.note)));
 
     }
 
@@ -360,6 +1590,7 @@ fn one_days_work(one_days_work: &OneDaysWork) -> Element<Message> {
         table = table.push(stop_col);
         table = table.push(duration_col);
         table = table.push(pause_col);
+        table = table.push(note_col);
 
 
     let one_days_work_widget = container(
@@ -377,15 +1608,10 @@ fn table_totals(app: &App) -> Element<'static, Message> {
     let sum_til_last_day = app.total_worked_hours();
 
     let delta = sum_til_last_day - should_hours;
-    let (hours_delta, minutes_delta) = compute_hours_and_minutes(delta);
-    let mut sign = "+";
-    if delta < 0. {
-        sign = "-";
-    }
-    let delta_label = format!("{sign} {}:{:0>2}", hours_delta.abs(), minutes_delta.abs());
+    let delta_label = format_contingent(delta);
 
     let work_all_times: Row<Message> = row!(
-        text("Contingent: "),
+        text(t("Contingent: ")),
         text(delta_label)
     )
         .padding(Padding{top: 5., right: 0., bottom:5., left:10.});
@@ -393,10 +1619,333 @@ fn table_totals(app: &App) -> Element<'static, Message> {
     let mut table: Column<Message> = Column::new();
         table = table.push(work_all_times);
 
+    let focus_blocks = app.calendar.get(&app.date.to_string()).map(OneDaysWork::focus_blocks).unwrap_or(0);
+    if focus_blocks > 0 {
+        table = table.push(row!(text(tf("Focus blocks: {count}", &[("count", &focus_blocks)])).size(13))
+            .padding(Padding{top: 0., right: 0., bottom:5., left:10.}));
+    }
+
     table.into()
 }
 
 
+fn reload_conflict_banner(conflict: Option<&ReloadConflict>) -> Element<'_, Message> {
+    let Some(conflict) = conflict else {
+        return Column::new().into();
+    };
+    let dates: Vec<String> = conflict.dates.iter().map(|date| date.to_string()).collect();
+
+    row!(
+        text(tf("The data file was changed elsewhere, conflicting edits on {dates}", &[("dates", &dates.join(", "))])).size(13),
+        horizontal_space(),
+        button(t("keep mine")).on_press(Message::KeepLocalChanges),
+        button(t("use file")).on_press(Message::UseFileChanges),
+    )
+    .spacing(10)
+    .padding(Padding::from(10))
+    .into()
+}
+
+
+fn idle_banner(period: Option<&IdlePeriod>) -> Element<'_, Message> {
+    let Some(period) = period else {
+        return Column::new().into();
+    };
+    let time = |timestamp: &jiff::Timestamp| format_time_of_day(&timestamp.to_zoned(jiff::tz::TimeZone::system()));
+    let minutes = period.duration().as_secs() / 60;
+
+    row!(
+        text(tf("You were away from {from} to {to} ({minutes} min)", &[("from", &time(&period.from)), ("to", &time(&period.to)), ("minutes", &minutes)])).size(13),
+        horizontal_space(),
+        button(t("keep")).on_press(Message::KeepIdleTime),
+        button(t("break")).on_press(Message::IdleAsBreak),
+        button(t("discard and stop")).on_press(Message::DiscardIdleTime),
+    )
+    .spacing(10)
+    .padding(Padding::from(10))
+    .into()
+}
+
+
+fn focus_banner(session: Option<&FocusSession>) -> Element<'_, Message> {
+    let Some(session) = session else {
+        return Column::new().into();
+    };
+    let (label, next) = match session.phase {
+        FocusPhase::Focus => (tf("Focus until {time}", &[("time", &format_time_of_day(&session.ends))]), None),
+        FocusPhase::Break => (tf("Break until {time}", &[("time", &format_time_of_day(&session.ends))]), None),
+        FocusPhase::Waiting => (t("Break is over").to_owned(), Some(button(t("next focus block")).on_press(Message::StartFocus))),
+    };
+
+    row!(
+        text(label).size(13),
+        horizontal_space(),
+    )
+    .push_maybe(next)
+    .push(button(t("end focus")).on_press(Message::StopFocus))
+    .spacing(10)
+    .padding(Padding::from(10))
+    .into()
+}
+
+
+fn palette_view<'a>(app: &App, palette: &'a Palette) -> Element<'a, Message> {
+    let mut list = Column::new().spacing(2);
+    for command in filter(commands(app), &palette.query) {
+        list = list.push(
+            button(row!(text(command.name).size(13), horizontal_space(), text(command.shortcut).size(12)))
+                .width(Length::Fill)
+                .on_press(Message::RunCommand(Box::new(command.message))),
+        );
+    }
+
+    column!(
+        row!(
+            text_input(t("type a command, enter runs the first"), &palette.query)
+                .id(text_input::Id::new(PALETTE_INPUT))
+                .on_input(Message::PaletteQueryChanged)
+                .on_submit(Message::SubmitPalette),
+            button(t("close")).on_press(Message::ClosePalette),
+        )
+        .spacing(10),
+        scrollable(list).height(Length::Fill),
+    )
+    .spacing(8)
+    .padding(Padding::from(10))
+    .into()
+}
+
+
+fn unlock_view(form: &UnlockForm) -> Element<'_, Message> {
+    let title = match form.new_passphrase {
+        true => t("Choose a passphrase to encrypt your work times"),
+        false => t("Enter the passphrase of your work times"),
+    };
+    let mut unlock_column = column!(
+        text(title),
+        text_input(t("passphrase"), &form.passphrase)
+            .secure(true)
+            .on_input(Message::PassphraseChanged)
+            .on_submit(Message::Unlock),
+    )
+    .spacing(8)
+    .padding(Padding::from(10));
+
+    if form.new_passphrase {
+        unlock_column = unlock_column.push(
+            text_input(t("repeat the passphrase"), &form.confirmation)
+                .secure(true)
+                .on_input(Message::PassphraseConfirmationChanged)
+                .on_submit(Message::Unlock),
+        );
+    }
+    if let Some(error) = &form.error {
+        unlock_column = unlock_column.push(text(error).size(13));
+    }
+    unlock_column
+        .push(vertical_space())
+        .push(row!(horizontal_space(), button(t("unlock")).on_press(Message::Unlock)))
+        .into()
+}
+
+
+fn recover_form(form: &RecoverForm) -> Element<'_, Message> {
+    let interval = &form.intervals[0];
+    let mut form_column = column!(
+        text(tf(
+            "The interval started on {date} at {time} was never stopped",
+            &[("date", &format_date(interval.date)), ("time", &format_time_of_day(&interval.start))]
+        )),
+        row!(
+            text(t("End")).width(60),
+            text_input("17:00", &form.end)
+                .on_input(Message::RecoverEndChanged)
+                .on_submit(Message::CloseDangling)
+                .width(80),
+            text(tf("suggested {time}", &[("time", &format_time_of_day(&interval.suggested_end))])).size(13),
+        )
+        .spacing(10),
+    )
+    .spacing(8)
+    .padding(Padding::from(10));
+
+    if form.intervals.len() > 1 {
+        form_column = form_column.push(text(tf("{count} more open intervals", &[("count", &(form.intervals.len() - 1))])).size(13));
+    }
+    if let Some(error) = &form.error {
+        form_column = form_column.push(text(error).size(13));
+    }
+    form_column
+        .push(vertical_space())
+        .push(
+            row!(
+                horizontal_space(),
+                button(t("discard interval")).on_press(Message::DiscardDangling),
+                button(t("close")).on_press(Message::CloseDangling),
+            )
+            .spacing(15),
+        )
+        .into()
+}
+
+
+fn correction_form(form: &CorrectionForm) -> Element<'_, Message> {
+    let mut form_column = column!(
+        text(tf("Edit interval on {date}", &[("date", &format_date(form.date))])),
+        row!(
+            text(t("Start")).width(60),
+            text_input("08:00", &form.start).on_input(Message::CorrectionStartChanged).width(80),
+            text(t("End")).width(40),
+            text_input("16:30", &form.end).on_input(Message::CorrectionEndChanged).width(80),
+        )
+        .spacing(10),
+        row!(
+            text(t("Reason")).width(60),
+            text_input(t("why the recorded times were wrong"), &form.reason)
+                .on_input(Message::CorrectionReasonChanged)
+                .on_submit(Message::SaveCorrection),
+        )
+        .spacing(10),
+        row!(
+            text(t("Note")).width(60),
+            text_input(t("what was done"), &form.note)
+                .on_input(Message::CorrectionNoteChanged)
+                .on_submit(Message::SaveCorrection),
+        )
+        .spacing(10),
+    )
+    .spacing(8)
+    .padding(Padding::from(10));
+
+    if let Some(error) = &form.error {
+        form_column = form_column.push(text(error).size(13));
+    }
+    form_column
+        .push(vertical_space())
+        .push(
+            row!(
+                horizontal_space(),
+                button(t("cancel")).on_press(Message::CancelCorrection),
+                button(t("save")).on_press(Message::SaveCorrection),
+            )
+            .spacing(15),
+        )
+        .into()
+}
+
+
+fn search_view(form: &SearchForm) -> Element<'_, Message> {
+    let query = &form.query;
+    let filters = column!(
+        row!(
+            text_input(t("text in labels and notes"), &query.text).on_input(Message::SearchTextChanged),
+            text_input(t("project"), &query.project).on_input(Message::SearchProjectChanged).width(110),
+            text_input("#tag", &query.tag).on_input(Message::SearchTagChanged).width(90),
+        )
+        .spacing(8),
+        row!(
+            pick_list(&Location::ALL[..], query.location, Message::SearchLocationSelected).placeholder(t("location")),
+            pick_list(&Absence::ALL[..], query.absence, Message::SearchAbsenceSelected).placeholder(t("absence")),
+            text_input(t("from"), &form.from).on_input(Message::SearchFromChanged).width(100),
+            text_input(t("to"), &form.to).on_input(Message::SearchToChanged).width(100),
+        )
+        .spacing(8),
+    )
+    .spacing(8);
+
+    let mut matches: Column<Message> = Column::new().spacing(2);
+    for found in &form.result.matches {
+        let times = match (&found.start, &found.end) {
+            (Some(start), Some(end)) => format!("{start} - {end}"),
+            (Some(start), None) => format!("{start} -"),
+            _ => String::new(),
+        };
+        let mut line = format!("{}   {times}   {}", format_date(found.date), format_hours(found.hours));
+        if let Some(label) = &found.label {
+            line += &format!("   {label}");
+        }
+        if !found.note.is_empty() {
+            line += &format!("   {}", found.note.replace('\n', " "));
+        }
+        // clicking a match opens its day
+        matches = matches.push(
+            button(text(line).size(13))
+                .on_press(Message::OpenDay(found.date))
+                .style(button::text)
+                .padding(0),
+        );
+    }
+
+    let mut search_column = column!(text(t("Search")), filters).spacing(8).padding(Padding::from(10));
+    if let Some(error) = &form.error {
+        search_column = search_column.push(text(error).size(13));
+    }
+    search_column
+        .push(scrollable(matches).height(Length::Fill))
+        .push(
+            row!(
+                text(tf(
+                    "{intervals} intervals on {days} days, {hours} h",
+                    &[
+                        ("intervals", &form.result.matches.len()),
+                        ("days", &form.result.days),
+                        ("hours", &format_hours(form.result.total_hours)),
+                    ]
+                )),
+                horizontal_space(),
+                button(t("clear")).on_press(Message::ClearSearchFilters),
+                button(t("close")).on_press(Message::CloseSearch),
+            )
+            .spacing(15),
+        )
+        .into()
+}
+
+
+fn import_preview(preview: &ImportPreview) -> Element<'_, Message> {
+    let intervals = preview.data.intervals.len();
+    let conflicts = preview.days.iter().filter(|day| day.has_conflict()).count();
+
+    let mut days: Column<Message> = Column::new().spacing(2);
+    for day in &preview.days {
+        let mut label = format!("{}   {}", format_date(day.date), tf("{count} intervals", &[("count", &day.imported)]));
+        if day.vacation {
+            label += t(", vacation");
+        }
+        if day.has_conflict() {
+            label += &format!("   {}", tf("! {existing} existing, {overlapping} overlapping", &[("existing", &day.existing), ("overlapping", &day.overlapping)]));
+        } else if day.existing > 0 {
+            label += &format!("   {}", tf("{existing} existing", &[("existing", &day.existing)]));
+        }
+        days = days.push(text(label).size(13));
+    }
+
+    let mut summary = tf(
+        "{intervals} intervals on {days} days, {conflicts} days overlap existing entries and need replace",
+        &[("intervals", &intervals), ("days", &preview.days.len()), ("conflicts", &conflicts)],
+    );
+    if preview.data.skipped > 0 {
+        summary += &tf(", {count} events skipped (all-day or unreadable times)", &[("count", &preview.data.skipped)]);
+    }
+
+    column!(
+        text(tf("Import {source} ({format})", &[("source", &preview.source), ("format", &preview.format)])),
+        text(summary).size(13),
+        scrollable(days).height(Length::Fill).width(Length::Fill),
+        row!(
+            horizontal_space(),
+            button(t("cancel")).on_press(Message::CancelImport),
+            button(t("replace")).on_press(Message::ApplyImport(ImportMode::Replace)),
+            button(t("merge")).on_press(Message::ApplyImport(ImportMode::Merge)),
+        )
+        .spacing(15),
+    )
+    .spacing(8)
+    .padding(Padding::from(10))
+    .into()
+}
+
+
 fn compute_sum_one_days_work(one_days_work: &OneDaysWork) -> String {
     let mut sum_duration = String::from("");
     if let Some(sum) = one_days_work.sum_work {
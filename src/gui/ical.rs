@@ -0,0 +1,246 @@
+use jiff::civil::DateTime;
+use jiff::tz::TimeZone;
+use jiff::Zoned;
+use serde::{Deserialize, Serialize};
+
+use crate::gui::csv_import::ImportedData;
+use crate::gui::gui_logic::{OneDaysWork, WorkTimes};
+use crate::gui::gui_main::Location;
+use crate::gui::serialize::location_label;
+use crate::i18n::in_any_language;
+
+// an interval read from an import, with the location of the day it belongs to if the source knows it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedInterval {
+    pub work_times: WorkTimes,
+    pub location: Option<Location>,
+}
+
+// the properties of a VEVENT while it is being read
+#[derive(Default)]
+struct PendingEvent {
+    start: Option<Zoned>,
+    end: Option<Zoned>,
+    summary: Option<String>,
+    location: Option<String>,
+    description: Option<String>,
+    // DTSTART or DTEND could not be read
+    invalid: bool,
+}
+
+// every finished interval becomes a VEVENT, open intervals are skipped
+pub fn write_ics(work_days: &[OneDaysWork]) -> String {
+    let now = utc_stamp(&Zoned::now());
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        "PRODID:-//time_tracker//work times//EN".to_owned(),
+        "CALSCALE:GREGORIAN".to_owned(),
+    ];
+
+    for work_day in work_days {
+        let location = location_label(work_day.location);
+        for work_times in &work_day.work_duration {
+            let (Some(start), Some(end)) = (&work_times.start, &work_times.end) else {
+                continue;
+            };
+            let summary = work_times.label.clone().unwrap_or("Work".to_owned());
+
+            lines.push("BEGIN:VEVENT".to_owned());
+            lines.push(format!("UID:{}@time-tracker", start.timestamp().as_second()));
+            lines.push(format!("DTSTAMP:{now}"));
+            lines.push(format!("DTSTART:{}", utc_stamp(start)));
+            lines.push(format!("DTEND:{}", utc_stamp(end)));
+            lines.push(format!("SUMMARY:{}", escape_text(&summary)));
+            if !location.is_empty() {
+                lines.push(format!("LOCATION:{}", escape_text(location)));
+            }
+            if !work_times.note.is_empty() {
+                lines.push(format!("DESCRIPTION:{}", escape_text(&work_times.note)));
+            }
+            lines.push("END:VEVENT".to_owned());
+        }
+    }
+    lines.push("END:VCALENDAR".to_owned());
+
+    let mut ics = String::new();
+    for line in lines {
+        ics += &fold_line(&line);
+    }
+    ics
+}
+
+// reads all timed VEVENTs. all-day events and events with times that can't be read are skipped and counted,
+// a single broken event doesn't stop the import
+pub fn parse_ics(content: &str) -> ImportedData {
+    let mut data = ImportedData::default();
+    let mut event: Option<PendingEvent> = None;
+
+    for line in unfold_lines(content) {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, params) = match name.split_once(';') {
+            Some((name, params)) => (name, params),
+            None => (name, ""),
+        };
+
+        match (name.to_ascii_uppercase().as_str(), &mut event) {
+            ("BEGIN", _) if value.eq_ignore_ascii_case("VEVENT") => event = Some(PendingEvent::default()),
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                let PendingEvent { start, end, summary, location, description, invalid } = event.take().unwrap();
+                let (Some(start), Some(end), false) = (start, end, invalid) else {
+                    data.skipped += 1;
+                    continue;
+                };
+                if end <= start {
+                    data.skipped += 1;
+                    continue;
+                }
+                let mut work_times = WorkTimes::init();
+                work_times.label = Some(summary.unwrap_or("Work".to_owned()));
+                work_times.start = Some(start);
+                work_times.end = Some(end);
+                work_times.note = description.unwrap_or_default();
+                data.intervals.push(ImportedInterval {
+                    work_times,
+                    location: location.as_deref().and_then(parse_location),
+                });
+            }
+            ("DTSTART", Some(event)) => match parse_date_time(params, value) {
+                Ok(start) => event.start = start,
+                Err(_) => event.invalid = true,
+            },
+            ("DTEND", Some(event)) => match parse_date_time(params, value) {
+                Ok(end) => event.end = end,
+                Err(_) => event.invalid = true,
+            },
+            ("SUMMARY", Some(event)) => event.summary = Some(unescape_text(value)),
+            ("LOCATION", Some(event)) => event.location = Some(unescape_text(value)),
+            ("DESCRIPTION", Some(event)) => event.description = Some(unescape_text(value)),
+            _ => {}
+        }
+    }
+    data
+}
+
+pub fn parse_location(input: &str) -> Option<Location> {
+    // exports and the ui may be in any of the languages
+    Location::ALL.into_iter().find(|location| {
+        in_any_language(input, location.name()) || in_any_language(input, location_label(Some(*location)))
+    })
+}
+
+fn utc_stamp(zoned: &Zoned) -> String {
+    zoned.with_time_zone(TimeZone::UTC).strftime("%Y%m%dT%H%M%SZ").to_string()
+}
+
+// supports UTC (`...Z`), `TZID=` and floating local times, dates without a time return None
+fn parse_date_time(params: &str, value: &str) -> Result<Option<Zoned>, String> {
+    if params.to_ascii_uppercase().contains("VALUE=DATE") && !params.to_ascii_uppercase().contains("VALUE=DATE-TIME") {
+        return Ok(None);
+    }
+    let error = |_| format!("invalid date time: {value}");
+
+    let (value, is_utc) = match value.strip_suffix('Z') {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+    let date_time = DateTime::strptime("%Y%m%dT%H%M%S", value).map_err(error)?;
+    let tzid = params
+        .split(';')
+        .find_map(|param| param.strip_prefix("TZID="))
+        .map(|tzid| tzid.trim_matches('"'));
+
+    let zoned = if is_utc {
+        date_time.to_zoned(TimeZone::UTC).map_err(error)?.with_time_zone(TimeZone::system())
+    } else if let Some(tzid) = tzid {
+        date_time.intz(windows_zone(tzid).unwrap_or(tzid)).map_err(error)?.with_time_zone(TimeZone::system())
+    } else {
+        date_time.to_zoned(TimeZone::system()).map_err(error)?
+    };
+    Ok(Some(zoned))
+}
+
+// outlook writes the windows names of time zones, these are the common ones
+fn windows_zone(tzid: &str) -> Option<&'static str> {
+    Some(match tzid {
+        "UTC" | "Coordinated Universal Time" => "UTC",
+        "GMT Standard Time" => "Europe/London",
+        "Greenwich Standard Time" => "Atlantic/Reykjavik",
+        "W. Europe Standard Time" => "Europe/Berlin",
+        "Central Europe Standard Time" => "Europe/Budapest",
+        "Central European Standard Time" => "Europe/Warsaw",
+        "Romance Standard Time" => "Europe/Paris",
+        "E. Europe Standard Time" => "Europe/Chisinau",
+        "FLE Standard Time" => "Europe/Kiev",
+        "GTB Standard Time" => "Europe/Bucharest",
+        "Turkey Standard Time" => "Europe/Istanbul",
+        "Russian Standard Time" => "Europe/Moscow",
+        "Eastern Standard Time" => "America/New_York",
+        "Central Standard Time" => "America/Chicago",
+        "Mountain Standard Time" => "America/Denver",
+        "US Mountain Standard Time" => "America/Phoenix",
+        "Pacific Standard Time" => "America/Los_Angeles",
+        "Alaskan Standard Time" => "America/Anchorage",
+        "Hawaiian Standard Time" => "Pacific/Honolulu",
+        "Atlantic Standard Time" => "America/Halifax",
+        "India Standard Time" => "Asia/Kolkata",
+        "China Standard Time" => "Asia/Shanghai",
+        "Singapore Standard Time" => "Asia/Singapore",
+        "Tokyo Standard Time" => "Asia/Tokyo",
+        "AUS Eastern Standard Time" => "Australia/Sydney",
+        _ => return None,
+    })
+}
+
+fn unfold_lines(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for line in content.lines() {
+        match line.strip_prefix(' ').or(line.strip_prefix('\t')) {
+            Some(continuation) if !lines.is_empty() => lines.last_mut().unwrap().push_str(continuation),
+            _ => lines.push(line.to_owned()),
+        }
+    }
+    lines
+}
+
+// lines longer than 75 bytes are folded as required by RFC 5545
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded += "\r\n ";
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded + "\r\n"
+}
+
+fn escape_text(input: &str) -> String {
+    input
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn unescape_text(input: &str) -> String {
+    let mut output = String::new();
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => output.push('\n'),
+                Some(other) => output.push(other),
+                None => {}
+            }
+        } else {
+            output.push(c);
+        }
+    }
+    output
+}
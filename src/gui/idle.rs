@@ -0,0 +1,142 @@
+use std::env;
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+use iced::futures::executor::block_on;
+use iced::futures::{SinkExt, Stream};
+use jiff::{SignedDuration, Timestamp};
+use serde::{Deserialize, Serialize};
+
+use crate::gui::gui_main::Message;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+// tells how long the user hasn't touched mouse or keyboard, tests use a scripted source
+pub trait IdleSource: Send {
+    // None if the source can't tell, e.g. the tool is missing or there is no session
+    fn idle_time(&mut self) -> Option<Duration>;
+}
+
+// the X11 screensaver extension through `xprintidle`, prints the idle time in milliseconds
+pub struct XScreenSaver;
+
+impl IdleSource for XScreenSaver {
+    fn idle_time(&mut self) -> Option<Duration> {
+        // without an X display there is nothing to ask
+        env::var_os("DISPLAY")?;
+        let output = Command::new("xprintidle").output().ok().filter(|output| output.status.success())?;
+        let millis = String::from_utf8_lossy(&output.stdout).trim().parse::<u64>().ok()?;
+        Some(Duration::from_millis(millis))
+    }
+}
+
+// the idle hint logind keeps for the session, set by the desktop after its idle delay
+pub struct LogindIdleHint;
+
+impl IdleSource for LogindIdleHint {
+    fn idle_time(&mut self) -> Option<Duration> {
+        let session = env::var("XDG_SESSION_ID").unwrap_or("self".to_owned());
+        let output = Command::new("loginctl")
+            .args(["show-session", &session, "-p", "IdleHint", "-p", "IdleSinceHint"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())?;
+        let (idle, since) = parse_idle_hint(&String::from_utf8_lossy(&output.stdout))?;
+        if !idle {
+            return Some(Duration::ZERO);
+        }
+        let since = Timestamp::from_microsecond(since).ok()?;
+        Timestamp::now().duration_since(since).try_into().ok()
+    }
+}
+
+// `IdleHint=yes` and `IdleSinceHint=<microseconds since the epoch>` as printed by loginctl
+pub fn parse_idle_hint(output: &str) -> Option<(bool, i64)> {
+    let value = |name: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(name).and_then(|line| line.strip_prefix('=')))
+            .map(str::trim)
+    };
+    let idle = value("IdleHint")? == "yes";
+    let since = value("IdleSinceHint")?.parse().ok()?;
+    Some((idle, since))
+}
+
+// asks the sources in order, the first one that knows wins
+pub struct SystemIdle {
+    sources: Vec<Box<dyn IdleSource>>,
+}
+
+impl Default for SystemIdle {
+    fn default() -> SystemIdle {
+        SystemIdle {
+            sources: vec![Box::new(XScreenSaver), Box::new(LogindIdleHint)],
+        }
+    }
+}
+
+impl IdleSource for SystemIdle {
+    fn idle_time(&mut self) -> Option<Duration> {
+        self.sources.iter_mut().find_map(|source| source.idle_time())
+    }
+}
+
+// the time the user was away, `to` is when they came back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdlePeriod {
+    pub from: Timestamp,
+    pub to: Timestamp,
+}
+
+impl IdlePeriod {
+    pub fn duration(&self) -> SignedDuration {
+        self.to.duration_since(self.from)
+    }
+}
+
+// turns the polled idle times into idle periods, a period is only reported once the user is back
+pub struct IdleDetector {
+    threshold: Duration,
+    idle_since: Option<Timestamp>,
+}
+
+impl IdleDetector {
+    pub fn new(threshold: Duration) -> IdleDetector {
+        IdleDetector {
+            threshold,
+            idle_since: None,
+        }
+    }
+
+    pub fn poll(&mut self, source: &mut dyn IdleSource, now: Timestamp) -> Option<IdlePeriod> {
+        let idle = source.idle_time()?;
+        let last_input = now.checked_sub(SignedDuration::try_from(idle).ok()?).ok()?;
+        if idle >= self.threshold {
+            self.idle_since.get_or_insert(last_input);
+            return None;
+        }
+        self.idle_since.take().map(|from| IdlePeriod { from, to: last_input })
+    }
+}
+
+// sends `Message::IdleDetected` when the user comes back after `minutes` without input,
+// only subscribed while an interval is running
+pub fn idle_stream(minutes: u32) -> impl Stream<Item = Message> {
+    iced::stream::channel(4, move |mut output| async move {
+        thread::spawn(move || {
+            let mut source = SystemIdle::default();
+            let mut detector = IdleDetector::new(Duration::from_secs(minutes as u64 * 60));
+            while !output.is_closed() {
+                thread::sleep(POLL_INTERVAL);
+                if let Some(period) = detector.poll(&mut source, Timestamp::now()) {
+                    if block_on(output.send(Message::IdleDetected(period))).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        std::future::pending::<()>().await
+    })
+}
@@ -0,0 +1,177 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use jiff::civil::Date;
+use jiff::Zoned;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::gui::csv_import::{parse_csv, ImportFormat, ImportedData};
+use crate::gui::gui_logic::OneDaysWork;
+use crate::gui::ical::parse_ics;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ImportMode {
+    // keep the existing intervals and add the imported ones
+    Merge,
+    // days contained in the import lose their existing intervals
+    Replace,
+}
+
+// an import file after reading, shown to the user before anything is changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPreview {
+    pub source: String,
+    pub format: ImportFormat,
+    pub data: ImportedData,
+    pub days: Vec<PreviewDay>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewDay {
+    pub date: Date,
+    pub imported: usize,
+    pub existing: usize,
+    // imported intervals that overlap an existing interval of that day
+    pub overlapping: usize,
+    pub vacation: bool,
+}
+
+impl PreviewDay {
+    // merging skips the overlapping intervals, only replacing imports them
+    pub fn has_conflict(&self) -> bool {
+        self.overlapping > 0
+    }
+}
+
+// asks for a file and reads it into a preview
+pub fn import(calendar: &HashMap<String, OneDaysWork>, config: &Config) -> Option<ImportPreview> {
+    let path_buf = rfd::FileDialog::new()
+        .add_filter("All supported files", &["csv", "ics"])
+        .add_filter("CSV (generic, Toggl, Clockify, Kimai, time tracker export)", &["csv"])
+        .add_filter("iCalendar", &["ics"])
+        .set_directory(config.get_export_dir())
+        .pick_file()?;
+
+    match preview_import(&path_buf, calendar) {
+        Ok(preview) => Some(preview),
+        Err(error) => {
+            println!("ERROR ::: import failed: {error}");
+            None
+        }
+    }
+}
+
+// the format is chosen by the file extension and for csv files by the header
+pub fn read_import(path: &Path) -> io::Result<(ImportFormat, ImportedData)> {
+    let content = fs::read_to_string(path)?;
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("ics") => {
+            Ok((ImportFormat::Ical, parse_ics(&content)))
+        }
+        Some("csv") | Some("txt") => parse_csv(&content).map_err(io::Error::other),
+        _ => Err(io::Error::other(format!("unsupported file type: {}", path.display()))),
+    }
+}
+
+pub fn preview_import(path: &Path, calendar: &HashMap<String, OneDaysWork>) -> io::Result<ImportPreview> {
+    let (format, data) = read_import(path)?;
+    let source = path
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    Ok(build_preview(source, format, data, calendar))
+}
+
+pub fn build_preview(source: String, format: ImportFormat, data: ImportedData, calendar: &HashMap<String, OneDaysWork>) -> ImportPreview {
+    let mut days = BTreeMap::<Date, PreviewDay>::new();
+    let preview_day = |date: Date| {
+        let existing = calendar.get(&date.to_string()).map(|work_day| work_day.work_duration.len()).unwrap_or(0);
+        PreviewDay {
+            date,
+            imported: 0,
+            existing,
+            overlapping: 0,
+            vacation: false,
+        }
+    };
+
+    for interval in &data.intervals {
+        let (Some(start), Some(end)) = (&interval.work_times.start, &interval.work_times.end) else {
+            continue;
+        };
+        let date = start.date();
+        let day = days.entry(date).or_insert_with(|| preview_day(date));
+        day.imported += 1;
+
+        if calendar.get(&date.to_string()).is_some_and(|work_day| work_day.overlaps(start, end)) {
+            day.overlapping += 1;
+        }
+    }
+    for date in &data.vacation_days {
+        days.entry(*date).or_insert_with(|| preview_day(*date)).vacation = true;
+    }
+
+    ImportPreview {
+        source,
+        format,
+        data,
+        days: days.into_values().collect(),
+    }
+}
+
+// returns the number of added intervals, with `Merge` intervals overlapping an existing one are skipped
+pub fn apply_import(calendar: &mut HashMap<String, OneDaysWork>, data: ImportedData, mode: ImportMode, config: &Config) -> usize {
+    let workday_span = config.get_workday_span();
+    if mode == ImportMode::Replace {
+        let mut replaced_days: Vec<Date> = data
+            .intervals
+            .iter()
+            .filter_map(|interval| interval.work_times.start.as_ref().map(Zoned::date))
+            .collect();
+        replaced_days.extend(&data.vacation_days);
+        for date in replaced_days {
+            if let Some(work_day) = calendar.get_mut(&date.to_string()) {
+                // a running interval is kept, imported intervals overlapping it are skipped
+                work_day.work_duration.retain(|work_times| work_times.end.is_none());
+                work_day.vacation = false;
+                work_day.added_workday = false;
+                work_day.recompute(workday_span);
+            }
+        }
+    }
+
+    for date in data.vacation_days {
+        let work_day = calendar.entry(date.to_string()).or_insert_with(|| OneDaysWork::init(date));
+        if !work_day.vacation {
+            work_day.vacation = true;
+            work_day.recompute(workday_span);
+        }
+    }
+
+    // an existing note is only replaced with `Replace`
+    for (date, note) in data.day_notes {
+        let work_day = calendar.entry(date.to_string()).or_insert_with(|| OneDaysWork::init(date));
+        if work_day.note.is_empty() || mode == ImportMode::Replace {
+            work_day.note = note;
+        }
+    }
+
+    let mut added = 0;
+    for interval in data.intervals {
+        let Some(start) = &interval.work_times.start else {
+            continue;
+        };
+        let date = start.date();
+        let work_day = calendar.entry(date.to_string()).or_insert_with(|| OneDaysWork::init(date));
+        if work_day.work_duration.is_empty() && interval.location.is_some() {
+            work_day.location = interval.location;
+        }
+        if work_day.add_interval(interval.work_times, workday_span) {
+            added += 1;
+        }
+    }
+    added
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use jiff::{Span, Timestamp};
+use serde::{Deserialize, Serialize};
+
+use crate::gui::crypto::{open_line, seal_line};
+use crate::gui::gui_logic::OneDaysWork;
+use crate::gui::serialize::data_path;
+use crate::gui::sync::{apply_changes, diff_calendars, Change};
+
+// one user action, `changes` turn the calendar before the action into the one after it
+// and `revert` turns it back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub at: Timestamp,
+    // `start`, `stop`, `location`, `undo`, ... see `App::save`
+    pub action: String,
+    pub changes: Vec<Change>,
+    pub revert: Vec<Change>,
+}
+
+// the append-only history of the calendar next to the data file, undo and redo only
+// reach the actions of the running session but are journaled like any other action
+#[derive(Debug, Clone)]
+pub struct Journal {
+    pub path: PathBuf,
+    undo: Vec<JournalEntry>,
+    redo: Vec<JournalEntry>,
+}
+
+impl Default for Journal {
+    fn default() -> Journal {
+        Journal::new(journal_path(&data_path()))
+    }
+}
+
+impl Journal {
+    pub fn new(path: PathBuf) -> Journal {
+        Journal {
+            path,
+            undo: vec![],
+            redo: vec![],
+        }
+    }
+
+    // journals the difference between the two calendars, returns the changes for the sync log
+    pub fn record(&mut self, action: &str, before: &HashMap<String, OneDaysWork>, after: &HashMap<String, OneDaysWork>) -> Vec<Change> {
+        let Some(entry) = self.write_entry(action, before, after) else {
+            return vec![];
+        };
+        let changes = entry.changes.clone();
+        self.undo.push(entry);
+        self.redo.clear();
+        changes
+    }
+
+    // journals changes made elsewhere, by another device or in the file. they can't be undone here and
+    // the actions before them can't either, their revert could silently drop the other side's edit
+    pub fn record_foreign(&mut self, action: &str, before: &HashMap<String, OneDaysWork>, after: &HashMap<String, OneDaysWork>) -> Vec<Change> {
+        let Some(entry) = self.write_entry(action, before, after) else {
+            return vec![];
+        };
+        self.undo.clear();
+        self.redo.clear();
+        entry.changes
+    }
+
+    fn write_entry(&self, action: &str, before: &HashMap<String, OneDaysWork>, after: &HashMap<String, OneDaysWork>) -> Option<JournalEntry> {
+        let entry = JournalEntry {
+            at: Timestamp::now(),
+            action: action.to_owned(),
+            changes: diff_calendars(before, after),
+            revert: diff_calendars(after, before),
+        };
+        if entry.changes.is_empty() {
+            return None;
+        }
+        self.append(&entry);
+        Some(entry)
+    }
+
+    // reverts the last action, returns the applied changes
+    pub fn undo(&mut self, calendar: &mut HashMap<String, OneDaysWork>, workday_span: Span) -> Option<Vec<Change>> {
+        let entry = self.undo.pop()?;
+        let changes = self.replay("undo", entry.revert.clone(), entry.changes.clone(), calendar, workday_span);
+        self.redo.push(entry);
+        Some(changes)
+    }
+
+    pub fn redo(&mut self, calendar: &mut HashMap<String, OneDaysWork>, workday_span: Span) -> Option<Vec<Change>> {
+        let entry = self.redo.pop()?;
+        let changes = self.replay("redo", entry.changes.clone(), entry.revert.clone(), calendar, workday_span);
+        self.undo.push(entry);
+        Some(changes)
+    }
+
+    fn replay(&mut self, action: &str, changes: Vec<Change>, revert: Vec<Change>, calendar: &mut HashMap<String, OneDaysWork>, workday_span: Span) -> Vec<Change> {
+        apply_changes(calendar, changes.clone(), workday_span);
+        self.append(&JournalEntry {
+            at: Timestamp::now(),
+            action: action.to_owned(),
+            changes: changes.clone(),
+            revert,
+        });
+        changes
+    }
+
+    fn append(&self, entry: &JournalEntry) {
+        let line = seal_line(&serde_json::to_string(entry).unwrap()) + "\n";
+        let written = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()));
+        if let Err(error) = written {
+            println!("ERROR ::: could not write journal {}: {error}", self.path.display());
+        }
+    }
+}
+
+pub fn journal_path(data_path: &Path) -> PathBuf {
+    data_path.with_file_name(".work_journal.jsonl")
+}
+
+pub fn read_journal(path: &Path) -> io::Result<Vec<JournalEntry>> {
+    let content = fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match open_line(line).and_then(|line| serde_json::from_str(&line).map_err(|error| error.to_string())) {
+            Ok(entry) => Some(entry),
+            Err(error) => {
+                println!("WARN ::: skipping journal line: {error}");
+                None
+            }
+        })
+        .collect())
+}
+
+// the calendar as the journal describes it, every entry applied in order
+pub fn rebuild(entries: Vec<JournalEntry>, workday_span: Span) -> HashMap<String, OneDaysWork> {
+    let mut calendar = HashMap::new();
+    apply_changes(&mut calendar, entries.into_iter().flat_map(|entry| entry.changes), workday_span);
+    calendar
+}
+
+// encrypts the lines of a journal written before encryption was turned on
+pub fn seal_journal(path: &Path) -> io::Result<()> {
+    let entries = match read_journal(path) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(error),
+    };
+    let lines: String = entries
+        .iter()
+        .map(|entry| seal_line(&serde_json::to_string(entry).unwrap()) + "\n")
+        .collect();
+    fs::write(path, lines)
+}
+
+// a journal starts with the data that existed before it, so it can always rebuild the whole calendar
+pub fn start_journal(path: &Path, calendar: &HashMap<String, OneDaysWork>) {
+    if path.exists() {
+        return;
+    }
+    let mut journal = Journal::new(path.to_path_buf());
+    journal.record("snapshot", &HashMap::new(), calendar);
+}
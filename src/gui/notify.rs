@@ -0,0 +1,158 @@
+use std::thread;
+use std::time::Duration;
+
+use iced::futures::executor::block_on;
+use iced::futures::{SinkExt, Stream};
+use jiff::civil::{Date, Weekday};
+use jiff::Zoned;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::gui::gui_logic::OneDaysWork;
+use crate::gui::gui_main::Message;
+use crate::i18n::{t, tf};
+use crate::utils::{format_hours, span_in_hours};
+
+const TICK_INTERVAL: Duration = Duration::from_secs(60);
+// the break the law asks for once the work of a day goes beyond `break_after_hours`
+const REQUIRED_BREAK_HOURS: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Reminder {
+    ClockIn,
+    TargetReached,
+    MaximumReached,
+    BreakDue,
+    StillRunning,
+}
+
+// the reminders already shown today, each one is shown once a day
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SentReminders {
+    pub date: Option<Date>,
+    pub reminders: Vec<Reminder>,
+}
+
+impl SentReminders {
+    // the due reminders not shown yet today, they count as shown from now on
+    pub fn take_new(&mut self, today: Date, due: Vec<Reminder>) -> Vec<Reminder> {
+        if self.date != Some(today) {
+            self.date = Some(today);
+            self.reminders.clear();
+        }
+        let new: Vec<Reminder> = due.into_iter().filter(|reminder| !self.reminders.contains(reminder)).collect();
+        self.reminders.extend(&new);
+        new
+    }
+}
+
+// today's work including the running interval up to `now`
+pub fn worked_hours(work_day: &OneDaysWork, now: &Zoned) -> f32 {
+    work_day
+        .work_duration
+        .iter()
+        .map(|work_times| match (&work_times.start, &work_times.end, &work_times.duration) {
+            (_, Some(_), Some(duration)) => span_in_hours(duration),
+            (Some(start), None, _) => start.until(now).map(|running| span_in_hours(&running)).unwrap_or(0.),
+            _ => 0.,
+        })
+        .sum()
+}
+
+// the reminders due for today at `now`, driven by the times and limits in the config
+pub fn due_reminders(config: &Config, today: Option<&OneDaysWork>, now: &Zoned) -> Vec<Reminder> {
+    let mut due = vec![];
+    let is_workday = !matches!(now.weekday(), Weekday::Saturday | Weekday::Sunday)
+        && !today.is_some_and(|work_day| work_day.vacation || work_day.added_workday);
+    let has_started = today.is_some_and(|work_day| !work_day.work_duration.is_empty());
+    let running = today
+        .and_then(|work_day| work_day.work_duration.last())
+        .is_some_and(|work_times| work_times.start.is_some() && work_times.end.is_none());
+
+    if config.clock_in_reminder.is_some_and(|time| now.time() >= time) && is_workday && !has_started {
+        due.push(Reminder::ClockIn);
+    }
+    let Some(work_day) = today else {
+        return due;
+    };
+    let worked = worked_hours(work_day, now);
+    let breaks = work_day.sum_pause.as_ref().map(span_in_hours).unwrap_or(0.);
+    let target = span_in_hours(&config.get_workday_span());
+
+    if running && target > 0. && worked >= target {
+        due.push(Reminder::TargetReached);
+    }
+    if running && config.max_daily_hours.is_some_and(|maximum| worked >= maximum) {
+        due.push(Reminder::MaximumReached);
+    }
+    if running && config.break_after_hours.is_some_and(|hours| worked >= hours) && breaks < REQUIRED_BREAK_HOURS {
+        due.push(Reminder::BreakDue);
+    }
+    if running && config.running_reminder.is_some_and(|time| now.time() >= time) {
+        due.push(Reminder::StillRunning);
+    }
+    due
+}
+
+pub fn reminder_text(reminder: Reminder, config: &Config) -> (String, String) {
+    let hours = |hours: Option<f32>| format_hours(hours.unwrap_or_default());
+    let (summary, body) = match reminder {
+        Reminder::ClockIn => ("Not clocked in yet", t("It's a workday and no work was recorded today.").to_owned()),
+        Reminder::TargetReached => (
+            "Daily target reached",
+            tf("You worked {hours} h today.", &[("hours", &format_hours(span_in_hours(&config.get_workday_span())))]),
+        ),
+        Reminder::MaximumReached => (
+            "Maximum working time reached",
+            tf("You worked {hours} h today, time to stop.", &[("hours", &hours(config.max_daily_hours))]),
+        ),
+        Reminder::BreakDue => (
+            "Break due",
+            tf("You worked {hours} h without a 30 minute break.", &[("hours", &hours(config.break_after_hours))]),
+        ),
+        Reminder::StillRunning => ("Timer still running", t("Did you forget to stop?").to_owned()),
+    };
+    (t(summary).to_owned(), body)
+}
+
+// shown on its own thread, a missing notification daemon must not block the app
+pub fn send_notification(summary: String, body: String) {
+    thread::spawn(move || {
+        #[cfg(target_os = "linux")]
+        if let Err(error) = notify_freedesktop(&summary, &body) {
+            println!("WARN ::: could not show notification `{summary}`: {error}");
+        }
+        #[cfg(not(target_os = "linux"))]
+        println!("{summary}: {body}");
+    });
+}
+
+#[cfg(target_os = "linux")]
+fn notify_freedesktop(summary: &str, body: &str) -> zbus::Result<()> {
+    use std::collections::HashMap;
+    use zbus::zvariant::Value;
+
+    let connection = zbus::blocking::Connection::session()?;
+    let hints: HashMap<&str, Value> = HashMap::new();
+    connection.call_method(
+        Some("org.freedesktop.Notifications"),
+        "/org/freedesktop/Notifications",
+        Some("org.freedesktop.Notifications"),
+        "Notify",
+        &("Time Tracker", 0u32, "", summary, body, Vec::<&str>::new(), hints, -1i32),
+    )?;
+    Ok(())
+}
+
+// sends `Message::Tick` every minute so reminders are checked even if nothing else happens
+pub fn tick_stream() -> impl Stream<Item = Message> {
+    iced::stream::channel(1, |mut output| async move {
+        thread::spawn(move || loop {
+            thread::sleep(TICK_INTERVAL);
+            if block_on(output.send(Message::Tick)).is_err() {
+                break;
+            }
+        });
+        std::future::pending::<()>().await
+    })
+}
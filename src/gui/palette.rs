@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+use crate::gui::gui_main::{App, Location, Message, State};
+use crate::gui::focus::FocusPhase;
+use crate::gui::appearance::ThemeChoice;
+use crate::i18n::t;
+
+// the id of the palette's text input, focused when the palette opens
+pub const PALETTE_INPUT: &str = "command palette";
+
+// lists every action of the app by name, opened with Ctrl+K
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Palette {
+    pub query: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct PaletteCommand {
+    pub name: String,
+    // the key doing the same outside the palette, empty if there is none
+    pub shortcut: &'static str,
+    pub message: Message,
+}
+
+// names and key names are translated, so the palette is searched in the language it is shown in
+fn command(name: &'static str, shortcut: &'static str, message: Message) -> PaletteCommand {
+    PaletteCommand { name: t(name).to_owned(), shortcut: t(shortcut), message }
+}
+
+// the actions possible right now, in the order they are listed
+pub fn commands(app: &App) -> Vec<PaletteCommand> {
+    let mut commands = vec![];
+    match app.state {
+        State::Stopped => commands.push(command("Start", "Space", Message::Start)),
+        State::Started => commands.push(command("Stop", "Space", Message::Stop)),
+        State::NotToday => {}
+    }
+    match (&app.state, &app.focus) {
+        (State::Stopped, Some(session)) if session.phase == FocusPhase::Waiting => {
+            commands.push(command("Start focus block", "", Message::StartFocus));
+            commands.push(command("End focus", "", Message::StopFocus));
+        }
+        (_, Some(_)) => commands.push(command("End focus", "", Message::StopFocus)),
+        (State::Stopped, None) => commands.push(command("Start focus block", "", Message::StartFocus)),
+        _ => {}
+    }
+    commands.extend([
+        command("Previous day", "←", Message::PreviousDay),
+        command("Next day", "→", Message::NextDay),
+        command("Today", "T", Message::Today),
+        command("Toggle vacation", "V", Message::ToggleVacation),
+        command("Toggle generic workday", "", Message::ToggleGenericWorkday),
+    ]);
+    for location in Location::ALL {
+        let name = match location {
+            Location::Homeoffice => "Location: home office",
+            Location::Office => "Location: office",
+        };
+        commands.push(command(name, "", Message::LocationSelected(location)));
+    }
+    commands.extend([
+        match app.config.compact {
+            true => command("Full window", "M", Message::ToggleCompact),
+            false => command("Compact mode", "M", Message::ToggleCompact),
+        },
+        command("Search", "", Message::OpenSearch),
+        command("Export", "E", Message::Export),
+        command("Export corrections", "", Message::ExportCorrections),
+        command("Import", "", Message::Import),
+        command("Undo", "Ctrl+Z", Message::Undo),
+        command("Redo", "Ctrl+Shift+Z", Message::Redo),
+        command("Quit", "", Message::Quit),
+    ]);
+    // theme names aren't translated, they are the names iced gives them
+    for choice in ThemeChoice::all().into_iter().filter(|choice| *choice != app.config.theme) {
+        commands.push(PaletteCommand {
+            name: format!("{}: {choice}", t("Theme")),
+            shortcut: "",
+            message: Message::ThemeSelected(choice),
+        });
+    }
+    commands
+}
+
+// the commands containing every word of the query, ignoring case
+pub fn filter(commands: Vec<PaletteCommand>, query: &str) -> Vec<PaletteCommand> {
+    let query = query.to_lowercase();
+    commands
+        .into_iter()
+        .filter(|command| {
+            let name = command.name.to_lowercase();
+            query.split_whitespace().all(|word| name.contains(word))
+        })
+        .collect()
+}
@@ -0,0 +1,251 @@
+use std::collections::BTreeMap;
+
+use jiff::civil::{Date, Weekday};
+use jiff::{ToSpan, Zoned};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::gui::corrections::{correction_lines, CorrectionLine};
+use crate::gui::gui_logic::OneDaysWork;
+use crate::gui::gui_main::Location;
+use crate::utils::{compute_should_hours, format_contingent, format_hours, format_iso_timestamp, span_in_hours};
+
+// bump when fields are removed or change their meaning, adding fields keeps the version
+pub const REPORT_SCHEMA_VERSION: u32 = 1;
+
+// the documented export for scripts, unlike `Calendar` it contains no jiff spans,
+// hours are decimal and times are ISO 8601 with offset
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RangeReport {
+    pub schema_version: u32,
+    pub generated_at: String,
+    pub from: Date,
+    pub to: Date,
+    pub hours_per_week: f32,
+    pub totals: Totals,
+    pub days: Vec<ReportDay>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Totals {
+    pub worked_hours: f64,
+    pub break_hours: f64,
+    pub target_hours: f64,
+    pub difference_hours: f64,
+    pub vacation_days: u32,
+    #[serde(default)]
+    pub focus_blocks: u32,
+    // overall balance since the configured start date at the end of the range
+    pub contingent_hours: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReportDay {
+    pub date: Date,
+    pub weekday: String,
+    // `home_office`, `office` or null
+    pub location: Option<String>,
+    pub vacation: bool,
+    pub generic_workday: bool,
+    pub worked_hours: f64,
+    pub break_hours: f64,
+    pub contingent_hours: f64,
+    #[serde(default)]
+    pub note: String,
+    // completed focus blocks, see `FocusSession`
+    #[serde(default)]
+    pub focus_blocks: u32,
+    pub intervals: Vec<ReportInterval>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReportInterval {
+    pub label: Option<String>,
+    pub start: Option<String>,
+    // null while the interval is still running
+    pub end: Option<String>,
+    pub duration_hours: Option<f64>,
+    pub break_hours: Option<f64>,
+    // empty unless the interval was changed after recording, see `CorrectionLine`
+    #[serde(default)]
+    pub corrections: Vec<CorrectionLine>,
+    #[serde(default)]
+    pub note: String,
+    #[serde(default)]
+    pub focus: bool,
+}
+
+// without a range all recorded days are reported
+pub fn build_range_report(config: &Config, work_days: &[OneDaysWork], from: Option<Date>, to: Option<Date>) -> RangeReport {
+    let from = from.or(work_days.first().map(|work_day| work_day.date)).unwrap_or(Zoned::now().date());
+    let to = to.or(work_days.last().map(|work_day| work_day.date)).unwrap_or(Zoned::now().date());
+
+    let mut report = RangeReport {
+        schema_version: REPORT_SCHEMA_VERSION,
+        generated_at: format_iso_timestamp(&Zoned::now()),
+        from,
+        to,
+        hours_per_week: config.hours_week,
+        totals: Totals {
+            worked_hours: 0.,
+            break_hours: 0.,
+            target_hours: 0.,
+            difference_hours: 0.,
+            vacation_days: 0,
+            focus_blocks: 0,
+            contingent_hours: 0.,
+        },
+        days: vec![],
+    };
+
+    let mut total_worked: f32 = 0.;
+    for work_day in work_days.iter().filter(|work_day| work_day.date <= to) {
+        let worked = work_day.sum_work.as_ref().map(span_in_hours).unwrap_or(0.);
+        total_worked += worked;
+        if work_day.date < from {
+            continue;
+        }
+        let contingent = total_worked - compute_should_hours(config.start_date, work_day.date, config);
+        let breaks = work_day.sum_pause.as_ref().map(span_in_hours).unwrap_or(0.);
+
+        report.totals.worked_hours += worked as f64;
+        report.totals.break_hours += breaks as f64;
+        report.totals.contingent_hours = decimal(contingent);
+        if work_day.vacation {
+            report.totals.vacation_days += 1;
+        }
+        report.totals.focus_blocks += work_day.focus_blocks() as u32;
+
+        report.days.push(ReportDay {
+            date: work_day.date,
+            weekday: work_day.date.strftime("%A").to_string(),
+            location: work_day.location.map(|location| match location {
+                Location::Homeoffice => "home_office".to_owned(),
+                Location::Office => "office".to_owned(),
+            }),
+            vacation: work_day.vacation,
+            generic_workday: work_day.added_workday,
+            worked_hours: decimal(worked),
+            break_hours: decimal(breaks),
+            contingent_hours: decimal(contingent),
+            note: work_day.note.clone(),
+            focus_blocks: work_day.focus_blocks() as u32,
+            intervals: work_day
+                .work_duration
+                .iter()
+                .map(|work_times| ReportInterval {
+                    label: work_times.label.clone(),
+                    start: work_times.start.as_ref().map(format_iso_timestamp),
+                    end: work_times.end.as_ref().map(format_iso_timestamp),
+                    duration_hours: work_times.duration.as_ref().map(|duration| decimal(span_in_hours(duration))),
+                    break_hours: work_times.pause.as_ref().map(|pause| decimal(span_in_hours(pause))),
+                    corrections: correction_lines(work_day.date, work_times),
+                    note: work_times.note.clone(),
+                    focus: work_times.focus,
+                })
+                .collect(),
+        });
+    }
+
+    let target_from = from.max(config.start_date);
+    if target_from <= to {
+        report.totals.target_hours = decimal(compute_should_hours(target_from, to, config));
+    }
+    report.totals.worked_hours = round_hours(report.totals.worked_hours);
+    report.totals.break_hours = round_hours(report.totals.break_hours);
+    report.totals.difference_hours = round_hours(report.totals.worked_hours - report.totals.target_hours);
+    report
+}
+
+pub fn write_json_report(report: &RangeReport) -> String {
+    serde_json::to_string_pretty(report).unwrap()
+}
+
+// one section per week, meant to be pasted into a standup note or a wiki page
+pub fn write_markdown_summary(report: &RangeReport, config: &Config) -> String {
+    let mut weeks = BTreeMap::<Date, Vec<&ReportDay>>::new();
+    for day in &report.days {
+        weeks.entry(week_start(day.date)).or_default().push(day);
+    }
+
+    let mut markdown = format!("# Work summary {} – {}\n", report.from, report.to);
+    for (monday, days) in weeks {
+        let sunday = monday.checked_add(6.days()).unwrap();
+        let week_from = monday.max(report.from).max(config.start_date);
+        let week_to = sunday.min(report.to);
+        let target = match week_from <= week_to {
+            true => compute_should_hours(week_from, week_to, config),
+            false => 0.,
+        };
+        let worked: f64 = days.iter().map(|day| day.worked_hours).sum();
+        let focus_blocks: u32 = days.iter().map(|day| day.focus_blocks).sum();
+
+        markdown += &format!("\n## Week {} ({monday} – {sunday})\n\n", monday.to_iso_week_date().week());
+        markdown += "| Day | Worked | Breaks | Location | Projects | Notes |\n";
+        markdown += "|-----|-------:|-------:|----------|----------|-------|\n";
+        for day in &days {
+            let location = match (day.vacation, day.location.as_deref()) {
+                (true, _) => "vacation",
+                (false, Some("home_office")) => "home office",
+                (false, Some(location)) => location,
+                (false, None) => "",
+            };
+            markdown += &format!(
+                "| {} {} | {} | {} | {location} | {} | {} |\n",
+                &day.weekday[..3],
+                day.date,
+                format_hours(day.worked_hours as f32),
+                format_hours(day.break_hours as f32),
+                projects(day).replace('|', "\\|"),
+                notes(day).replace('|', "\\|"),
+            );
+        }
+        markdown += &format!(
+            "\n**Total:** {} h (target {} h, {})\n",
+            format_hours(worked as f32),
+            format_hours(target),
+            format_contingent(worked as f32 - target)
+        );
+        if focus_blocks > 0 {
+            markdown += &format!("**Focus blocks:** {focus_blocks}\n");
+        }
+    }
+    markdown
+}
+
+// the labels of a day with their summed hours, like `Website 3:30, Work 4:00`
+fn projects(day: &ReportDay) -> String {
+    let mut hours = BTreeMap::<&str, f64>::new();
+    for interval in &day.intervals {
+        let label = interval.label.as_deref().unwrap_or("Work");
+        *hours.entry(label).or_default() += interval.duration_hours.unwrap_or(0.);
+    }
+    hours
+        .into_iter()
+        .map(|(label, hours)| format!("{label} {}", format_hours(hours as f32)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// the day's note followed by the notes of its intervals
+fn notes(day: &ReportDay) -> String {
+    std::iter::once(day.note.as_str())
+        .chain(day.intervals.iter().map(|interval| interval.note.as_str()))
+        .filter(|note| !note.is_empty())
+        .map(|note| note.replace('\n', " "))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+pub fn week_start(date: Date) -> Date {
+    let days_since_monday = date.weekday().since(Weekday::Monday);
+    date.checked_sub(days_since_monday.days()).unwrap()
+}
+
+fn decimal(hours: f32) -> f64 {
+    round_hours(hours as f64)
+}
+
+fn round_hours(hours: f64) -> f64 {
+    (hours * 100.).round() / 100.
+}
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use jiff::civil::Date;
+use jiff::{Span, Timestamp, Zoned};
+use serde::{Deserialize, Serialize};
+
+use crate::gui::gui_logic::OneDaysWork;
+
+// the reason recorded with the correction when a dangling interval is closed
+pub const RECOVER_REASON: &str = "left open, closed at startup";
+
+// an interval of a past day that was never stopped, e.g. the app was killed or the machine died
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DanglingInterval {
+    pub date: Date,
+    pub index: usize,
+    pub start: Zoned,
+    // the last known activity on that day, or a full workday if there is none
+    pub suggested_end: Zoned,
+}
+
+// asks for the end of one dangling interval after the other
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoverForm {
+    pub intervals: Vec<DanglingInterval>,
+    // `HH:MM` for the first interval
+    pub end: String,
+    pub error: Option<String>,
+}
+
+impl RecoverForm {
+    pub fn new(intervals: Vec<DanglingInterval>) -> Option<RecoverForm> {
+        let first = intervals.first()?;
+        Some(RecoverForm {
+            end: first.suggested_end.strftime("%H:%M").to_string(),
+            intervals,
+            error: None,
+        })
+    }
+
+    // the form for the remaining intervals, None when all are done
+    pub fn next(mut self) -> Option<RecoverForm> {
+        self.intervals.remove(0);
+        RecoverForm::new(self.intervals)
+    }
+}
+
+// open intervals on days before `today`, oldest first. `activities` are the times of the journaled actions
+pub fn find_dangling(calendar: &HashMap<String, OneDaysWork>, today: Date, activities: &[Timestamp], workday_span: Span) -> Vec<DanglingInterval> {
+    let mut dangling = vec![];
+    for work_day in calendar.values().filter(|work_day| work_day.date < today) {
+        for (index, work_times) in work_day.work_duration.iter().enumerate() {
+            let (Some(start), None) = (&work_times.start, &work_times.end) else {
+                continue;
+            };
+            dangling.push(DanglingInterval {
+                date: work_day.date,
+                index,
+                start: start.clone(),
+                suggested_end: suggested_end(start, activities, workday_span),
+            });
+        }
+    }
+    dangling.sort_by_key(|interval| interval.start.timestamp());
+    dangling
+}
+
+// the last action on the interval's day, the start itself is journaled too and doesn't count
+fn suggested_end(start: &Zoned, activities: &[Timestamp], workday_span: Span) -> Zoned {
+    let end_of_day = start.date().at(23, 59, 0, 0).to_zoned(start.time_zone().clone()).unwrap();
+    let after_start = start.checked_add(Span::new().minutes(1)).unwrap();
+    let last_activity = activities
+        .iter()
+        .map(|activity| activity.to_zoned(start.time_zone().clone()))
+        .filter(|activity| *activity > after_start && activity.date() == start.date())
+        .max();
+    match last_activity {
+        Some(activity) => activity,
+        None => start.checked_add(workday_span).unwrap().min(end_of_day),
+    }
+}
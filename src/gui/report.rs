@@ -0,0 +1,434 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use jiff::civil::Date;
+use jiff::Zoned;
+
+use crate::config::Config;
+use crate::gui::gui_logic::OneDaysWork;
+use crate::gui::serialize::location_label;
+use crate::i18n::Language;
+use crate::utils::{
+    compute_should_hours, contingent_until, format_contingent, format_hours, format_time_of_day, month_target_end, month_target_hours,
+    span_in_hours,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Html,
+    Pdf,
+}
+
+const HEADINGS: [&str; 9] = [
+    "Date", "Weekday", "Location", "Start", "End", "Duration", "Break", "Day total", "Contingent",
+];
+
+const CORRECTION_NOTE: &str = "* corrected after recording, see the corrections report";
+
+// one printed line of the timesheet, the first line of a day carries the day's columns
+struct ReportLine {
+    cells: [String; 9],
+    // the day's note on its first line and the interval's note, too long for a column in the pdf
+    note: String,
+}
+
+struct MonthlyReport {
+    language: Language,
+    employee: String,
+    period: String,
+    lines: Vec<ReportLine>,
+    worked: f32,
+    breaks: f32,
+    target: f32,
+    vacation_days: u32,
+    contingent: f32,
+    // intervals changed after recording are marked with `*`
+    has_corrections: bool,
+}
+
+// a signed timesheet for the month containing `month`, the contingent is carried over from all previous days
+pub fn export_report(path: &Path, config: &Config, work_days: Vec<OneDaysWork>, month: Date, format: ReportFormat) -> io::Result<()> {
+    let report = build_report(config, work_days, month);
+    match format {
+        ReportFormat::Html => fs::write(path, render_html(&report)),
+        ReportFormat::Pdf => fs::write(path, render_pdf(&report)),
+    }
+}
+
+fn build_report(config: &Config, work_days: Vec<OneDaysWork>, month: Date) -> MonthlyReport {
+    let first_day = month.first_of_month();
+    let last_day = month.last_of_month();
+
+    let language = config.language;
+    let mut report = MonthlyReport {
+        language,
+        employee: config.employee_name.clone(),
+        period: format!("{first_day} - {last_day}"),
+        lines: vec![],
+        worked: 0.,
+        breaks: 0.,
+        target: 0.,
+        vacation_days: 0,
+        contingent: 0.,
+        has_corrections: false,
+    };
+
+    let mut total_worked: f32 = 0.;
+    for work_day in work_days.iter().filter(|work_day| work_day.date <= last_day) {
+        let day_worked = work_day.sum_work.as_ref().map(span_in_hours).unwrap_or(0.);
+        total_worked += day_worked;
+        if work_day.date < first_day {
+            continue;
+        }
+        let contingent = total_worked - compute_should_hours(config.start_date, work_day.date, config);
+        report.worked += day_worked;
+        report.breaks += work_day.sum_pause.as_ref().map(span_in_hours).unwrap_or(0.);
+
+        let mut location = language.tr(location_label(work_day.location)).to_owned();
+        if work_day.vacation {
+            report.vacation_days += 1;
+            location = language.tr("Vacation").to_owned();
+        } else if work_day.added_workday {
+            location = format!("{location} ({})", language.tr("generic workday"));
+        }
+
+        let mut day_cells = Some([
+            work_day.date.to_string(),
+            language.weekday(work_day.date.weekday()).to_owned(),
+            location,
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            work_day.sum_work.as_ref().map(|sum| format_hours(span_in_hours(sum))).unwrap_or_default(),
+            format_contingent(contingent),
+        ]);
+
+        let mut day_note = Some(work_day.note.clone());
+        for work_times in &work_day.work_duration {
+            let mut cells = day_cells.take().unwrap_or_default();
+            let notes = [day_note.take().unwrap_or_default(), work_times.note.clone()];
+            cells[3] = work_times.start.as_ref().map(format_time_of_day).unwrap_or_default();
+            if work_times.is_corrected() {
+                cells[3] += " *";
+                report.has_corrections = true;
+            }
+            cells[4] = work_times.end.as_ref().map(format_time_of_day).unwrap_or_default();
+            cells[5] = work_times.duration.as_ref().map(|duration| format_hours(span_in_hours(duration))).unwrap_or_default();
+            cells[6] = work_times.pause.as_ref().map(|pause| format_hours(span_in_hours(pause))).unwrap_or_default();
+            report.lines.push(ReportLine {
+                cells,
+                note: join_notes(&notes),
+            });
+        }
+        if let Some(cells) = day_cells {
+            report.lines.push(ReportLine {
+                cells,
+                note: day_note.unwrap_or_default(),
+            });
+        }
+    }
+
+    // days without records at the end of the month still count towards the target
+    let today = Zoned::now().date();
+    report.target = month_target_hours(month, today, config);
+    report.contingent = contingent_until(total_worked, month_target_end(month, today), config);
+    report
+}
+
+fn join_notes(notes: &[String]) -> String {
+    notes.iter().filter(|note| !note.is_empty()).cloned().collect::<Vec<_>>().join("; ")
+}
+
+fn summary_rows(report: &MonthlyReport) -> [(&'static str, String); 5] {
+    let language = report.language;
+    [
+        (language.tr("Worked"), format_hours(report.worked)),
+        (language.tr("Breaks"), format_hours(report.breaks)),
+        (language.tr("Target"), format_hours(report.target)),
+        (language.tr("Vacation days"), report.vacation_days.to_string()),
+        (language.tr("Contingent at end of period"), format_contingent(report.contingent)),
+    ]
+}
+
+fn escape_html(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_html(report: &MonthlyReport) -> String {
+    let language = report.language;
+    let title = language.tr("Timesheet");
+    let mut html = format!("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n");
+    html += "<style>\n\
+         body { font-family: sans-serif; font-size: 10pt; margin: 2cm; }\n\
+         table { border-collapse: collapse; width: 100%; }\n\
+         th, td { border-bottom: 1px solid #999; padding: 2px 6px; text-align: left; }\n\
+         .summary { width: auto; margin-top: 1em; }\n\
+         .signatures { display: flex; gap: 4cm; margin-top: 3cm; }\n\
+         .signature { border-top: 1px solid #000; width: 6cm; padding-top: 4px; }\n\
+         @page { size: A4; margin: 1.5cm; }\n\
+         </style>\n</head>\n<body>\n";
+    html += &format!("<h1>{title}</h1>\n");
+    html += &format!(
+        "<p>{}<br>{}</p>\n",
+        language.tf("Employee: {name}", &[("name", &escape_html(&report.employee))]),
+        language.tf("Period: {period}", &[("period", &report.period)])
+    );
+
+    html += "<table>\n<tr>";
+    for heading in HEADINGS {
+        html += &format!("<th>{}</th>", language.tr(heading));
+    }
+    html += &format!("<th>{}</th></tr>\n", language.tr("Note"));
+    for line in &report.lines {
+        html += "<tr>";
+        for cell in &line.cells {
+            html += &format!("<td>{}</td>", escape_html(cell));
+        }
+        html += &format!("<td>{}</td></tr>\n", escape_html(&line.note));
+    }
+    html += "</table>\n";
+    if report.has_corrections {
+        html += &format!("<p>{}</p>\n", language.tr(CORRECTION_NOTE));
+    }
+
+    html += "<table class=\"summary\">\n";
+    for (label, value) in summary_rows(report) {
+        html += &format!("<tr><th>{label}</th><td>{value}</td></tr>\n");
+    }
+    html += "</table>\n";
+
+    html += "<div class=\"signatures\">\n";
+    html += &format!("<div class=\"signature\">{}</div>\n", language.tr("Date, signature employee"));
+    html += &format!("<div class=\"signature\">{}</div>\n", language.tr("Date, signature supervisor"));
+    html += "</div>\n</body>\n</html>\n";
+    html
+}
+
+const PAGE_WIDTH: f32 = 595.;
+const PAGE_HEIGHT: f32 = 842.;
+const MARGIN: f32 = 50.;
+const LINE_HEIGHT: f32 = 13.;
+const COLUMN_X: [f32; 9] = [0., 62., 120., 220., 260., 300., 345., 390., 440.];
+// notes are written from the start column to the right margin at 8pt, helvetica averages about half the font size per character
+const NOTE_CHARS: usize = ((PAGE_WIDTH - 2. * MARGIN - 220.) / 4.5) as usize;
+
+// a single page A4 layout with the builtin Helvetica font, pages are added when the table gets too long
+struct PdfWriter {
+    pages: Vec<String>,
+    current: String,
+    y: f32,
+}
+
+impl PdfWriter {
+    fn new() -> PdfWriter {
+        PdfWriter {
+            pages: vec![],
+            current: String::new(),
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            self.pages.push(std::mem::take(&mut self.current));
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+
+    fn text(&mut self, x: f32, size: f32, bold: bool, text: &str) {
+        let font = if bold { "F2" } else { "F1" };
+        self.current += &format!(
+            "BT /{font} {size} Tf {:.1} {:.1} Td ({}) Tj ET\n",
+            MARGIN + x,
+            self.y,
+            escape_pdf(text)
+        );
+    }
+
+    fn line(&mut self, x_from: f32, x_to: f32) {
+        self.current += &format!(
+            "{:.1} {:.1} m {:.1} {:.1} l S\n",
+            MARGIN + x_from,
+            self.y,
+            MARGIN + x_to,
+            self.y
+        );
+    }
+
+    fn new_line(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.pages.push(self.current);
+
+        let mut objects: Vec<String> = vec![];
+        let page_count = self.pages.len();
+        let kids: Vec<String> = (0..page_count).map(|i| format!("{} 0 R", 5 + 2 * i)).collect();
+
+        objects.push("<< /Type /Catalog /Pages 2 0 R >>".to_owned());
+        objects.push(format!("<< /Type /Pages /Kids [{}] /Count {page_count} >>", kids.join(" ")));
+        objects.push("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_owned());
+        objects.push("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>".to_owned());
+        for (i, content) in self.pages.iter().enumerate() {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                6 + 2 * i
+            ));
+            objects.push(format!("<< /Length {} >>\nstream\n{content}endstream", to_win_ansi(content).len()));
+        }
+
+        let mut pdf: Vec<u8> = b"%PDF-1.4\n".to_vec();
+        let mut offsets = vec![];
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend(format!("{} 0 obj\n", i + 1).as_bytes());
+            pdf.extend(to_win_ansi(object));
+            pdf.extend(b"\nendobj\n");
+        }
+        let xref_offset = pdf.len();
+        pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+        for offset in offsets {
+            pdf.extend(format!("{offset:010} 00000 n \n").as_bytes());
+        }
+        pdf.extend(
+            format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref_offset}\n%%EOF\n", objects.len() + 1).as_bytes(),
+        );
+        pdf
+    }
+}
+
+// the builtin fonts use windows-1252, which differs from latin-1 in 0x80-0x9f. everything else is replaced
+fn to_win_ansi(input: &str) -> Vec<u8> {
+    input
+        .chars()
+        .map(|c| match c {
+            '€' => 0x80,
+            '‚' => 0x82,
+            'ƒ' => 0x83,
+            '„' => 0x84,
+            '…' => 0x85,
+            '†' => 0x86,
+            '‡' => 0x87,
+            'ˆ' => 0x88,
+            '‰' => 0x89,
+            'Š' => 0x8a,
+            '‹' => 0x8b,
+            'Œ' => 0x8c,
+            'Ž' => 0x8e,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            '˜' => 0x98,
+            '™' => 0x99,
+            'š' => 0x9a,
+            '›' => 0x9b,
+            'œ' => 0x9c,
+            'ž' => 0x9e,
+            'Ÿ' => 0x9f,
+            // control characters where windows-1252 has the signs above
+            '\u{80}'..='\u{9f}' => b'?',
+            c if (c as u32) < 256 => c as u8,
+            _ => b'?',
+        })
+        .collect()
+}
+
+// splits a text at spaces into lines of at most `max_chars`, longer words are cut
+fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        while word.len() > max_chars {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            lines.push(word.drain(..max_chars).collect());
+        }
+        let word: String = word.into_iter().collect();
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > max_chars {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line += &word;
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn escape_pdf(input: &str) -> String {
+    input.replace('\\', "\\\\").replace('(', "\\(").replace(')', "\\)")
+}
+
+fn render_pdf(report: &MonthlyReport) -> Vec<u8> {
+    let language = report.language;
+    let mut pdf = PdfWriter::new();
+
+    pdf.text(0., 16., true, language.tr("Timesheet"));
+    pdf.new_line(24.);
+    pdf.text(0., 10., false, &language.tf("Employee: {name}", &[("name", &report.employee)]));
+    pdf.new_line(LINE_HEIGHT);
+    pdf.text(0., 10., false, &language.tf("Period: {period}", &[("period", &report.period)]));
+    pdf.new_line(2. * LINE_HEIGHT);
+
+    for (x, heading) in COLUMN_X.iter().zip(HEADINGS) {
+        pdf.text(*x, 9., true, language.tr(heading));
+    }
+    pdf.new_line(4.);
+    pdf.line(0., PAGE_WIDTH - 2. * MARGIN);
+    pdf.new_line(LINE_HEIGHT - 4.);
+
+    for line in &report.lines {
+        pdf.ensure_space(LINE_HEIGHT);
+        for (x, cell) in COLUMN_X.iter().zip(&line.cells) {
+            pdf.text(*x, 9., false, cell);
+        }
+        pdf.new_line(LINE_HEIGHT);
+        for note_line in wrap(&line.note, NOTE_CHARS) {
+            pdf.ensure_space(LINE_HEIGHT);
+            pdf.text(COLUMN_X[3], 8., false, &note_line);
+            pdf.new_line(LINE_HEIGHT);
+        }
+    }
+
+    if report.has_corrections {
+        pdf.ensure_space(2. * LINE_HEIGHT);
+        pdf.new_line(4.);
+        pdf.text(0., 8., false, language.tr(CORRECTION_NOTE));
+        pdf.new_line(LINE_HEIGHT);
+    }
+
+    pdf.ensure_space(6. * LINE_HEIGHT);
+    pdf.new_line(LINE_HEIGHT);
+    for (label, value) in summary_rows(report) {
+        pdf.text(0., 10., true, label);
+        pdf.text(170., 10., false, &value);
+        pdf.new_line(LINE_HEIGHT);
+    }
+
+    pdf.ensure_space(90.);
+    pdf.new_line(60.);
+    pdf.line(0., 200.);
+    pdf.line(260., 460.);
+    pdf.new_line(LINE_HEIGHT);
+    pdf.text(0., 9., false, language.tr("Date, signature employee"));
+    pdf.text(260., 9., false, language.tr("Date, signature supervisor"));
+
+    pdf.finish()
+}
@@ -0,0 +1,169 @@
+use jiff::civil::Date;
+use serde::{Deserialize, Serialize};
+
+use crate::gui::gui_logic::{OneDaysWork, WorkTimes};
+use crate::gui::gui_main::Location;
+use crate::i18n::t;
+use crate::utils::{format_time_of_day, span_in_hours};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Absence {
+    Vacation,
+    GenericWorkday,
+    // days without vacation or generic workday
+    None,
+}
+
+impl Absence {
+    pub(crate) const ALL: [Absence; 3] = [Absence::Vacation, Absence::GenericWorkday, Absence::None];
+
+    fn of(work_day: &OneDaysWork) -> Absence {
+        match (work_day.vacation, work_day.added_workday) {
+            (true, _) => Absence::Vacation,
+            (false, true) => Absence::GenericWorkday,
+            (false, false) => Absence::None,
+        }
+    }
+}
+
+impl std::fmt::Display for Absence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            t(match self {
+                Absence::Vacation => "vacation",
+                Absence::GenericWorkday => "generic workday",
+                Absence::None => "no absence",
+            })
+        )
+    }
+}
+
+// what the search view filters by, every filter left empty matches everything.
+// the project is the interval's label, tags are words like `#support` in the notes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchQuery {
+    pub text: String,
+    pub project: String,
+    pub tag: String,
+    pub location: Option<Location>,
+    pub absence: Option<Absence>,
+    pub from: Option<Date>,
+    pub to: Option<Date>,
+}
+
+// the search view's inputs, dates stay text until they are valid
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchForm {
+    pub query: SearchQuery,
+    pub from: String,
+    pub to: String,
+    pub result: SearchResult,
+    pub error: Option<String>,
+}
+
+impl SearchForm {
+    // reads the date fields into the query, an empty field is an open end
+    pub fn parse_dates(&mut self) -> Result<(), String> {
+        let parse = |input: &str| match input.trim() {
+            "" => Ok(None),
+            input => input.parse::<Date>().map(Some).map_err(|_| format!("{input} is not a date like 2024-03-01")),
+        };
+        self.query.from = parse(&self.from)?;
+        self.query.to = parse(&self.to)?;
+        Ok(())
+    }
+}
+
+// an interval matching the search, days without intervals like vacation days have no times
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchMatch {
+    pub date: Date,
+    pub label: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub hours: f32,
+    pub note: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub matches: Vec<SearchMatch>,
+    pub total_hours: f32,
+    pub days: usize,
+}
+
+// matches in date order, `work_days` as returned by `sorted_work_days`
+pub fn search(work_days: &[OneDaysWork], query: &SearchQuery) -> SearchResult {
+    let mut result = SearchResult::default();
+    for work_day in work_days.iter().filter(|work_day| day_matches(work_day, query)) {
+        let day_text = contains(&work_day.note, &query.text);
+        let matches_before = result.matches.len();
+
+        for work_times in &work_day.work_duration {
+            let text_matches = day_text
+                || contains(&work_times.note, &query.text)
+                || work_times.label.as_deref().is_some_and(|label| contains(label, &query.text));
+            let project_matches = query.project.trim().is_empty()
+                || work_times.label.as_deref().unwrap_or("Work").eq_ignore_ascii_case(query.project.trim());
+            let tag_matches = has_tag(&work_day.note, &query.tag) || has_tag(&work_times.note, &query.tag);
+            if text_matches && project_matches && tag_matches {
+                result.matches.push(interval_match(work_day.date, work_times));
+            }
+        }
+        // a day without intervals, like a vacation day, has no project
+        let day_only = work_day.work_duration.is_empty() && query.project.trim().is_empty();
+        if day_only && day_text && has_tag(&work_day.note, &query.tag) {
+            result.matches.push(SearchMatch {
+                date: work_day.date,
+                label: None,
+                start: None,
+                end: None,
+                hours: work_day.sum_work.as_ref().map(span_in_hours).unwrap_or(0.),
+                note: work_day.note.clone(),
+            });
+        }
+
+        if result.matches.len() > matches_before {
+            result.days += 1;
+        }
+    }
+    result.total_hours = result.matches.iter().map(|found| found.hours).sum();
+    result
+}
+
+fn day_matches(work_day: &OneDaysWork, query: &SearchQuery) -> bool {
+    query.from.is_none_or(|from| work_day.date >= from)
+        && query.to.is_none_or(|to| work_day.date <= to)
+        && query.location.is_none_or(|location| work_day.location == Some(location))
+        && query.absence.is_none_or(|absence| Absence::of(work_day) == absence)
+}
+
+fn interval_match(date: Date, work_times: &WorkTimes) -> SearchMatch {
+    SearchMatch {
+        date,
+        label: work_times.label.clone(),
+        start: work_times.start.as_ref().map(format_time_of_day),
+        end: work_times.end.as_ref().map(format_time_of_day),
+        hours: work_times.duration.as_ref().map(span_in_hours).unwrap_or(0.),
+        note: work_times.note.clone(),
+    }
+}
+
+fn contains(text: &str, query: &str) -> bool {
+    text.to_lowercase().contains(&query.trim().to_lowercase())
+}
+
+fn has_tag(text: &str, tag: &str) -> bool {
+    let tag = tag.trim().trim_start_matches('#');
+    tag.is_empty() || tags(text).any(|found| found.eq_ignore_ascii_case(tag))
+}
+
+// the words starting with `#`, without the `#` and trailing punctuation
+pub fn tags(text: &str) -> impl Iterator<Item = &str> {
+    text.split_whitespace()
+        .filter_map(|word| word.strip_prefix('#'))
+        .map(|tag| tag.trim_end_matches(|c: char| !c.is_alphanumeric() && c != '-' && c != '_'))
+        .filter(|tag| !tag.is_empty())
+}
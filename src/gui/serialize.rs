@@ -1,12 +1,19 @@
 use crate::config::Config;
+use crate::gui::crypto::{read_data, write_data};
 use crate::gui::gui_logic::OneDaysWork;
-use crate::utils::{compute_hours_and_minutes, compute_should_hours, format_duration};
-use jiff::{SpanRound, Unit, Zoned};
+use crate::gui::ical::write_ics;
+use crate::gui::range_report::{build_range_report, week_start, write_json_report, write_markdown_summary};
+use crate::gui::report::{export_report, ReportFormat};
+use crate::gui::xlsx::export_xlsx;
+use crate::utils::{compute_hours_and_minutes, compute_should_hours, format_duration, span_in_hours};
+use jiff::civil::Date;
+use jiff::{ToSpan, Unit, Zoned};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::io;
+use std::path::{Path, PathBuf};
 use crate::gui::gui_main::Location;
 
 #[derive(Serialize, Deserialize)]
@@ -15,7 +22,7 @@ pub struct Calendar {
 }
 
 impl Calendar {
-    fn to_hashmap(self) -> HashMap<String, OneDaysWork> {
+    pub fn into_hashmap(self) -> HashMap<String, OneDaysWork> {
         let mut map = HashMap::<String, OneDaysWork>::new();
         for one_days_work in self.work_days.into_iter() {
             map.insert(one_days_work.date.to_string(), one_days_work);
@@ -23,71 +30,60 @@ impl Calendar {
         map
     }
 
-    pub fn update(cal_map: &HashMap<String, OneDaysWork>) {
-        let mut work_days:Vec<OneDaysWork> = cal_map.values().cloned().collect();
-        work_days.sort_by(|a, b| a.date.cmp(&b.date));
+    pub fn update(cal_map: &HashMap<String, OneDaysWork>, path: &Path) {
+        let work_days = sorted_work_days(cal_map);
 
         let cal = Calendar {
             work_days,
         };
 
-        let mut path: PathBuf = env::current_exe().unwrap();
-        path.set_file_name(".work_data");
-        path.set_extension("json");
         let serialized = serde_json::to_string(&cal).unwrap();
-        fs::write(path, serialized).unwrap();
+        write_data(path, &serialized).unwrap();
     }
 }
 
-pub fn init_calendar() -> HashMap<String, OneDaysWork> {
+pub fn sorted_work_days(cal_map: &HashMap<String, OneDaysWork>) -> Vec<OneDaysWork> {
+    let mut work_days: Vec<OneDaysWork> = cal_map.values().cloned().collect();
+    work_days.sort_by_key(|a| a.date);
+    work_days
+}
+
+pub fn data_path() -> PathBuf {
     let mut path: PathBuf = env::current_exe().unwrap();
     path.set_file_name(".work_data");
     path.set_extension("json");
+    path
+}
 
+pub fn init_calendar(path: &Path) -> HashMap<String, OneDaysWork> {
     let today = Zoned::now().date();
-    match read_calendar(&path) {
+    match read_calendar(path) {
         None => {
             let mut calendar = HashMap::new();
             calendar.insert(
                 today.to_string(),
-                OneDaysWork {
-                    date: today,
-                    location: Some(Location::Homeoffice),
-                    work_duration: vec![],
-                    sum_work: None,
-                    sum_pause: None,
-                    vacation: false,
-                    added_workday: false,
-                },
+                OneDaysWork::init(today),
             );
             calendar
         }
         Some(calendar) => {
-            let mut calendar = calendar.to_hashmap();
+            let mut calendar = calendar.into_hashmap();
             match calendar.get_mut(&Zoned::now().date().to_string()) {
                 Some(_) => calendar,
                 None => {
                     calendar.insert(
                         today.to_string(),
-                        OneDaysWork {
-                            date: today,
-                            location: Some(Location::Homeoffice),
-                            work_duration: vec![],
-                            sum_work: None,
-                            sum_pause: None,
-                            vacation: false,
-                            added_workday: false,
-                        },
+                        OneDaysWork::init(today),
                     );
-                    return calendar;
+                    calendar
                 }
             }
         }
     }
 }
 
-pub fn read_calendar(path: &PathBuf) -> Option<Calendar> {
-    match fs::read_to_string(&path) {
+pub fn read_calendar(path: &Path) -> Option<Calendar> {
+    match read_data(path) {
         Err(error) => {
             print!("{}", error);
             None
@@ -98,34 +94,88 @@ pub fn read_calendar(path: &PathBuf) -> Option<Calendar> {
     }
 }
 
-pub fn export(config: &Config) {
+// unlike `read_calendar` a half written or broken file is an error instead of a panic,
+// used when the file is reloaded while the app is running
+pub fn try_read_calendar(path: &Path) -> Result<Calendar, String> {
+    let file_content = read_data(path).map_err(|error| error.to_string())?;
+    serde_json::from_str(&file_content).map_err(|error| error.to_string())
+}
+
+// the selected date picks the month of the printable timesheet and the week of the markdown summary
+pub fn export(config: &mut Config, selected: Date) {
     let path_buf = rfd::FileDialog::new()
-        .set_file_name("work_times_export.csv")
-        // TODO: anderes default dir setzen
-        .set_directory("/Users/jesse/Desktop")
+        .add_filter("CSV", &["csv"])
+        .add_filter("Excel timesheet", &["xlsx"])
+        .add_filter("Printable monthly timesheet", &["html", "pdf"])
+        .add_filter("iCalendar", &["ics"])
+        .add_filter("JSON report", &["json"])
+        .add_filter("Markdown weekly summary", &["md"])
+        .set_file_name(config.get_export_file_name())
+        .set_directory(config.get_export_dir())
         .save_file();
 
     if let Some(path_buf) = path_buf {
-        let mut path: PathBuf = env::current_exe().unwrap();
-        path.set_file_name(".work_data");
-        path.set_extension("json");
-
-        let mut write_string = String::from(";;;;;;\n");
-
-        if let Some(work_days) = read_calendar(&path) {
-            let mut total_worked: f32 = 0.;
-            for work_day in work_days.work_days.into_iter() {
-                if let Some(sum_work) = work_day.sum_work {
-                    let minutes = sum_work
-                        .round(SpanRound::new().largest(Unit::Minute))
-                        .unwrap()
-                        .get_minutes() as f32;
-                    total_worked += minutes / 60.;
-                }
-                write_string = write_string + &serialize_to_csv(work_day, config, total_worked);
-            }
+        match export_to(&path_buf, config, selected, DateRange::default()) {
+            Ok(()) => config.remember_export_path(&path_buf),
+            Err(error) => println!("ERROR ::: export failed: {error}"),
         }
-        fs::write(path_buf, write_string).unwrap();
+    }
+}
+
+// limits the json report and the markdown summary, open ends fall back to the format's default
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DateRange {
+    pub from: Option<Date>,
+    pub to: Option<Date>,
+}
+
+// writes the export without any dialog, used by the gui and the `--export` command line option.
+// the format is chosen by the file extension, everything unknown is written as csv
+pub fn export_to(path_buf: &Path, config: &Config, selected: Date, range: DateRange) -> io::Result<()> {
+    let work_days = match read_calendar(&data_path()) {
+        Some(calendar) => calendar.work_days,
+        None => vec![],
+    };
+
+    match path_buf.extension().and_then(|extension| extension.to_str()) {
+        Some("xlsx") => export_xlsx(path_buf, config, work_days),
+        Some("html") | Some("htm") => export_report(path_buf, config, work_days, selected, ReportFormat::Html),
+        Some("pdf") => export_report(path_buf, config, work_days, selected, ReportFormat::Pdf),
+        Some("ics") => fs::write(path_buf, write_ics(&work_days)),
+        Some("json") => {
+            let report = build_range_report(config, &work_days, range.from, range.to);
+            fs::write(path_buf, write_json_report(&report))
+        }
+        Some("md") => {
+            let from = range.from.unwrap_or(week_start(selected));
+            let to = range.to.unwrap_or(from.checked_add(6.days()).unwrap());
+            let report = build_range_report(config, &work_days, Some(from), Some(to));
+            fs::write(path_buf, write_markdown_summary(&report, config))
+        }
+        _ => fs::write(path_buf, serialize_calendar_to_csv(work_days, config)),
+    }
+}
+
+pub fn serialize_calendar_to_csv(work_days: Vec<OneDaysWork>, config: &Config) -> String {
+    let mut write_string = String::from(";;;;;;\n");
+
+    let mut total_worked: f32 = 0.;
+    for work_day in work_days.into_iter() {
+        if let Some(sum_work) = work_day.sum_work {
+            total_worked += span_in_hours(&sum_work);
+        }
+        write_string = write_string + &serialize_to_csv(work_day, config, total_worked);
+    }
+    write_string
+}
+
+pub fn location_label(location: Option<Location>) -> &'static str {
+    match location {
+        Some(location) => match location {
+            Location::Homeoffice => "Home office",
+            Location::Office => "In office",
+        },
+        None => "",
     }
 }
 
@@ -136,38 +186,47 @@ fn serialize_to_csv(todays_work: OneDaysWork, config: &Config, sum_til_last_day:
 
 
     let sum_work = match todays_work.sum_work.as_ref() {
-        Some(sum_work) => &format_duration(&sum_work),
+        Some(sum_work) => &format_duration(sum_work),
         None => "",
     };
     let sum_pause = match todays_work.sum_pause.as_ref() {
-        Some(sum_pause) => &format_duration(&sum_pause),
+        Some(sum_pause) => &format_duration(sum_pause),
         None => "",
     };
     let contingent = sum_til_last_day - should_hours;
     let (hours, minutes) = compute_hours_and_minutes(contingent);
 
 
+    // headings and labels in the configured language, dates stay ISO so the file sorts and imports anywhere
+    let language = config.language;
     let vacation = match todays_work.vacation {
-        true => "VACATION",
+        true => language.tr("VACATION"),
         false => "",
     };
 
-    let location = match todays_work.location {
-        Some(location) => match location{
-            Location::Homeoffice => "Home office",
-            Location::Office => "In office",
-        },
-        None => "",
-    };
+    let location = language.tr(location_label(todays_work.location));
+    // the day's work as a decimal number, with a decimal comma where the language uses one
+    let decimal_hours = language.decimal(todays_work.sum_work.as_ref().map(span_in_hours).unwrap_or_default(), 2);
 
-    let mut write_string = format!("{date};;;;;;\n");
+    let mut write_string = format!("{date};;;;;;{}\n", csv_note(&todays_work.note));
     write_string = write_string
         + &format!(
-            "{location};SUM WORK;{sum_work};SUM BREAKS;{sum_pause};CONTINGENT;{hours} : {:0>2}\n",
-            minutes.abs()
+            "{location};{};{sum_work};{};{sum_pause};{};{hours} : {:0>2};{};{decimal_hours}\n",
+            language.tr("SUM WORK"),
+            language.tr("SUM BREAKS"),
+            language.tr("CONTINGENT"),
+            minutes.abs(),
+            language.tr("HOURS"),
         );
     write_string = write_string + &format!("{vacation};;;;;;\n");
-    write_string = write_string + ";START;END;;DURATION;BREAK;\n";
+    write_string += &format!(
+        ";{};{};;{};{};{}\n",
+        language.tr("START"),
+        language.tr("END"),
+        language.tr("DURATION"),
+        language.tr("BREAK"),
+        language.tr("NOTE")
+    );
 
     for work_times in &todays_work.work_duration {
         let start = match work_times.start.as_ref() {
@@ -179,16 +238,25 @@ fn serialize_to_csv(todays_work: OneDaysWork, config: &Config, sum_til_last_day:
             None => "",
         };
         let duration = match work_times.duration.as_ref() {
-            Some(duration) => &format_duration(&duration),
+            Some(duration) => &format_duration(duration),
             None => "",
         };
         let pause = match work_times.pause.as_ref() {
-            Some(pause) => &format_duration(&pause),
+            Some(pause) => &format_duration(pause),
             None => "",
         };
-        write_string = write_string + &format!(";{start};{end};;{duration};{pause};\n")
+        let corrected = match work_times.is_corrected() {
+            true => language.tr("CORRECTED"),
+            false => "",
+        };
+        write_string = write_string + &format!(";{start};{end};{corrected};{duration};{pause};{}\n", csv_note(&work_times.note))
     }
 
-    write_string = write_string + "\n";
+    write_string += "\n";
     write_string
 }
+
+// notes are free text, they must not break the line or the columns
+pub fn csv_note(note: &str) -> String {
+    note.replace(';', ",").replace(['\r', '\n'], " ")
+}
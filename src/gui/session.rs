@@ -0,0 +1,163 @@
+use std::thread;
+
+use iced::futures::executor::block_on;
+use iced::futures::{SinkExt, Stream};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::gui::gui_main::Message;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SessionEvent {
+    Lock,
+    Unlock,
+    Suspend,
+    Resume,
+    Shutdown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionAction {
+    Stop,
+    Start,
+    Nothing,
+}
+
+// what to do with the timer on a session event. only an interval the app stopped itself is started again,
+// `auto_stopped` tells if the last stop came from a session event
+pub fn session_action(config: &Config, event: SessionEvent, running: bool, auto_stopped: bool) -> SessionAction {
+    match event {
+        SessionEvent::Suspend | SessionEvent::Shutdown if running && config.stop_on_suspend => SessionAction::Stop,
+        SessionEvent::Lock if running && config.stop_on_lock => SessionAction::Stop,
+        SessionEvent::Resume | SessionEvent::Unlock if !running && auto_stopped && config.start_on_resume => SessionAction::Start,
+        _ => SessionAction::Nothing,
+    }
+}
+
+// the journal action of a stop caused by the event, see `App::save`
+pub fn stop_action(event: SessionEvent) -> &'static str {
+    match event {
+        SessionEvent::Lock => "stop on lock",
+        SessionEvent::Shutdown => "stop on shutdown",
+        _ => "stop on suspend",
+    }
+}
+
+// sends `Message::SessionEvent` for the events logind reports, nothing on other systems
+pub fn session_stream() -> impl Stream<Item = Message> {
+    iced::stream::channel(4, |mut output| async move {
+        thread::spawn(move || {
+            let send = |event| block_on(output.send(Message::SessionEvent(event))).is_ok();
+            #[cfg(target_os = "linux")]
+            if let Err(error) = logind::listen(send) {
+                println!("WARN ::: no session events from logind: {error}");
+            }
+            #[cfg(not(target_os = "linux"))]
+            drop(send);
+        });
+        std::future::pending::<()>().await
+    })
+}
+
+#[cfg(target_os = "linux")]
+mod logind {
+    use std::collections::HashMap;
+    use std::thread;
+    use std::time::Duration;
+
+    use zbus::blocking::{Connection, MessageIterator};
+    use zbus::message::Type;
+    use zbus::zvariant::{OwnedFd, OwnedObjectPath, OwnedValue};
+    use zbus::MatchRule;
+
+    use super::SessionEvent;
+
+    const LOGIN1: &str = "org.freedesktop.login1";
+    const MANAGER_PATH: &str = "/org/freedesktop/login1";
+    const MANAGER: &str = "org.freedesktop.login1.Manager";
+    // how long suspend and shutdown wait for the app to write the stopped interval
+    const WRITE_DELAY: Duration = Duration::from_secs(1);
+
+    // blocks until the connection to the system bus ends or `send` returns false
+    pub fn listen(mut send: impl FnMut(SessionEvent) -> bool) -> zbus::Result<()> {
+        let connection = Connection::system()?;
+        let session = own_session(&connection);
+        let rule = MatchRule::builder().msg_type(Type::Signal).sender(LOGIN1)?.build();
+        let messages = MessageIterator::for_match_rule(rule, &connection, None)?;
+        // a delay lock makes logind wait for us before the machine sleeps
+        let mut inhibitor = inhibit(&connection);
+
+        for message in messages {
+            let message = message?;
+            let header = message.header();
+            let member = header.member().map(|member| member.as_str()).unwrap_or_default();
+            let from_session = session.as_ref().is_some_and(|session| header.path().is_some_and(|path| path.as_str() == session.as_str()));
+
+            let event = match member {
+                "PrepareForSleep" => match message.body().deserialize::<bool>()? {
+                    true => Some(SessionEvent::Suspend),
+                    false => Some(SessionEvent::Resume),
+                },
+                "PrepareForShutdown" if message.body().deserialize::<bool>()? => Some(SessionEvent::Shutdown),
+                "Lock" if from_session => Some(SessionEvent::Lock),
+                "Unlock" if from_session => Some(SessionEvent::Unlock),
+                // desktops that lock the screen themselves only set the hint
+                "PropertiesChanged" if from_session => {
+                    let (_, changed, _) = message.body().deserialize::<(String, HashMap<String, OwnedValue>, Vec<String>)>()?;
+                    match changed.get("LockedHint").and_then(|locked| bool::try_from(locked).ok()) {
+                        Some(true) => Some(SessionEvent::Lock),
+                        Some(false) => Some(SessionEvent::Unlock),
+                        None => None,
+                    }
+                }
+                _ => None,
+            };
+            let Some(event) = event else {
+                continue;
+            };
+            if !send(event) {
+                break;
+            }
+            match event {
+                SessionEvent::Suspend | SessionEvent::Shutdown => {
+                    thread::sleep(WRITE_DELAY);
+                    inhibitor = None;
+                }
+                SessionEvent::Resume if inhibitor.is_none() => inhibitor = inhibit(&connection),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn own_session(connection: &Connection) -> Option<OwnedObjectPath> {
+        let session_id = std::env::var("XDG_SESSION_ID").unwrap_or("auto".to_owned());
+        let reply = connection
+            .call_method(Some(LOGIN1), MANAGER_PATH, Some(MANAGER), "GetSessionByPID", &(std::process::id()))
+            .or_else(|_| connection.call_method(Some(LOGIN1), MANAGER_PATH, Some(MANAGER), "GetSession", &(session_id.as_str())));
+        match reply.and_then(|reply| reply.body().deserialize::<OwnedObjectPath>()) {
+            Ok(path) => Some(path),
+            Err(error) => {
+                println!("WARN ::: no logind session, lock and unlock are not reported: {error}");
+                None
+            }
+        }
+    }
+
+    fn inhibit(connection: &Connection) -> Option<OwnedFd> {
+        let reply = connection.call_method(
+            Some(LOGIN1),
+            MANAGER_PATH,
+            Some(MANAGER),
+            "Inhibit",
+            &("sleep:shutdown", "Time Tracker", "ending the running interval", "delay"),
+        );
+        match reply.and_then(|reply| reply.body().deserialize::<OwnedFd>()) {
+            Ok(fd) => Some(fd),
+            Err(error) => {
+                println!("WARN ::: could not delay suspend: {error}");
+                None
+            }
+        }
+    }
+}
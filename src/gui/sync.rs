@@ -0,0 +1,315 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use iced::futures::executor::block_on;
+use iced::futures::{SinkExt, Stream};
+use jiff::civil::Date;
+use jiff::{Span, Timestamp, Zoned};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::gui::gui_logic::{Correction, OneDaysWork, WorkTimes};
+use crate::gui::gui_main::{Location, Message};
+use crate::gui::watch::same_day;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+const LOG_EXTENSION: &str = "jsonl";
+
+// one edit of the calendar, intervals are identified by their start so the same interval
+// edited on two devices ends up as one interval and new intervals of both devices coexist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Change {
+    Day {
+        date: Date,
+        location: Option<Location>,
+        vacation: bool,
+        added_workday: bool,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        note: String,
+    },
+    Interval {
+        start: Zoned,
+        end: Option<Zoned>,
+        label: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        corrections: Vec<Correction>,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        note: String,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        focus: bool,
+    },
+    RemoveInterval {
+        start: Zoned,
+    },
+    RemoveDay {
+        date: Date,
+    },
+}
+
+// a line of a device's log, `seq` counts the entries of that device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub device: String,
+    pub seq: u64,
+    pub at: Timestamp,
+    pub change: Change,
+}
+
+// the sync directory if syncing is on. the logs would carry the work times in plaintext and every device
+// encrypts with its own key, so an encrypted data file turns syncing off
+pub fn sync_dir(config: &Config) -> Option<&PathBuf> {
+    config.sync_dir.as_ref().filter(|_| !config.encrypt_data)
+}
+
+// every device only ever appends to its own file, so a sync tool never has to merge a file
+pub fn log_path(sync_dir: &Path, device: &str) -> PathBuf {
+    sync_dir.join(format!("{device}.{LOG_EXTENSION}"))
+}
+
+// the changes turning `before` into `after`
+pub fn diff_calendars(before: &HashMap<String, OneDaysWork>, after: &HashMap<String, OneDaysWork>) -> Vec<Change> {
+    let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    let mut changes = vec![];
+
+    for key in keys {
+        let (old, new) = (before.get(key), after.get(key));
+        if same_day(old, new) {
+            continue;
+        }
+        let Some(new) = new else {
+            changes.push(Change::RemoveDay { date: old.unwrap().date });
+            continue;
+        };
+
+        let day_changed = old.is_none_or(|old| {
+            old.location != new.location || old.vacation != new.vacation || old.added_workday != new.added_workday || old.note != new.note
+        });
+        if day_changed {
+            changes.push(Change::Day {
+                date: new.date,
+                location: new.location,
+                vacation: new.vacation,
+                added_workday: new.added_workday,
+                note: new.note.clone(),
+            });
+        }
+
+        let old_intervals = old.map(|old| old.work_duration.as_slice()).unwrap_or_default();
+        for work_times in &new.work_duration {
+            let Some(start) = &work_times.start else {
+                continue;
+            };
+            let unchanged = find_interval(old_intervals, start)
+                .is_some_and(|old| {
+                    old.end.as_ref().map(Zoned::timestamp) == work_times.end.as_ref().map(Zoned::timestamp)
+                        && old.label == work_times.label
+                        && old.corrections == work_times.corrections
+                        && old.note == work_times.note
+                        && old.focus == work_times.focus
+                });
+            if !unchanged {
+                changes.push(Change::Interval {
+                    start: start.clone(),
+                    end: work_times.end.clone(),
+                    label: work_times.label.clone(),
+                    corrections: work_times.corrections.clone(),
+                    note: work_times.note.clone(),
+                    focus: work_times.focus,
+                });
+            }
+        }
+        for work_times in old_intervals {
+            if let Some(start) = &work_times.start {
+                if find_interval(&new.work_duration, start).is_none() {
+                    changes.push(Change::RemoveInterval { start: start.clone() });
+                }
+            }
+        }
+    }
+    changes
+}
+
+fn find_interval<'a>(intervals: &'a [WorkTimes], start: &Zoned) -> Option<&'a WorkTimes> {
+    intervals
+        .iter()
+        .find(|work_times| work_times.start.as_ref().map(Zoned::timestamp) == Some(start.timestamp()))
+}
+
+// appends the changes to this device's log
+pub fn append_changes(config: &Config, changes: Vec<Change>) -> io::Result<()> {
+    let Some(sync_dir) = sync_dir(config) else {
+        return Ok(());
+    };
+    if changes.is_empty() {
+        return Ok(());
+    }
+    let path = log_path(sync_dir, &config.device_name);
+    let first_seq = match fs::read_to_string(&path) {
+        Ok(content) => content.lines().count() as u64,
+        Err(_) => 0,
+    };
+
+    let mut lines = String::new();
+    let at = Timestamp::now();
+    for (seq, change) in (first_seq..).zip(changes) {
+        let entry = LogEntry {
+            device: config.device_name.clone(),
+            seq,
+            at,
+            change,
+        };
+        lines += &serde_json::to_string(&entry).unwrap();
+        lines.push('\n');
+    }
+    OpenOptions::new().create(true).append(true).open(path)?.write_all(lines.as_bytes())
+}
+
+// the entries of all devices, a line a sync tool hasn't finished copying is skipped
+pub fn read_logs(sync_dir: &Path) -> Vec<LogEntry> {
+    let mut entries = vec![];
+    for path in log_files(sync_dir) {
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            match serde_json::from_str::<LogEntry>(line) {
+                Ok(entry) => entries.push(entry),
+                Err(error) => println!("WARN ::: skipping sync log line in {}: {error}", path.display()),
+            }
+        }
+    }
+    entries
+}
+
+fn log_files(sync_dir: &Path) -> Vec<PathBuf> {
+    let Ok(dir) = fs::read_dir(sync_dir) else {
+        return vec![];
+    };
+    let mut paths: Vec<PathBuf> = dir
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == LOG_EXTENSION))
+        .collect();
+    paths.sort();
+    paths
+}
+
+// replays the entries of all devices sorted by time, device and sequence number,
+// so every device builds the same calendar no matter in which order the logs arrived
+pub fn merge_logs(mut entries: Vec<LogEntry>, workday_span: Span) -> HashMap<String, OneDaysWork> {
+    entries.sort_by(|a, b| (a.at, &a.device, a.seq).cmp(&(b.at, &b.device, b.seq)));
+
+    let mut calendar = HashMap::<String, OneDaysWork>::new();
+    apply_changes(&mut calendar, entries.into_iter().map(|entry| entry.change), workday_span);
+    calendar
+}
+
+// applies changes to a calendar and recomputes the sums of the days they touched
+pub fn apply_changes(calendar: &mut HashMap<String, OneDaysWork>, changes: impl IntoIterator<Item = Change>, workday_span: Span) {
+    let mut touched = BTreeSet::new();
+    for change in changes {
+        touched.insert(change_date(&change));
+        match change {
+            Change::Day { date, location, vacation, added_workday, note } => {
+                let work_day = calendar.entry(date.to_string()).or_insert_with(|| OneDaysWork::init(date));
+                work_day.location = location;
+                work_day.vacation = vacation;
+                work_day.added_workday = added_workday;
+                work_day.note = note;
+            }
+            Change::Interval { start, end, label, corrections, note, focus } => {
+                let date = start.date();
+                let work_day = calendar.entry(date.to_string()).or_insert_with(|| OneDaysWork::init(date));
+                let existing = work_day
+                    .work_duration
+                    .iter_mut()
+                    .find(|work_times| work_times.start.as_ref().map(Zoned::timestamp) == Some(start.timestamp()));
+                match existing {
+                    Some(work_times) => {
+                        work_times.end = end;
+                        work_times.label = label;
+                        work_times.corrections = corrections;
+                        work_times.note = note;
+                        work_times.focus = focus;
+                        work_times.duration = None;
+                    }
+                    None => work_day.work_duration.push(WorkTimes {
+                        label,
+                        start: Some(start),
+                        end,
+                        corrections,
+                        note,
+                        focus,
+                        ..WorkTimes::init()
+                    }),
+                }
+            }
+            Change::RemoveInterval { start } => {
+                if let Some(work_day) = calendar.get_mut(&start.date().to_string()) {
+                    work_day
+                        .work_duration
+                        .retain(|work_times| work_times.start.as_ref().map(Zoned::timestamp) != Some(start.timestamp()));
+                }
+            }
+            Change::RemoveDay { date } => {
+                calendar.remove(&date.to_string());
+            }
+        }
+    }
+
+    for date in touched {
+        if let Some(work_day) = calendar.get_mut(&date.to_string()) {
+            work_day.recompute(workday_span);
+        }
+    }
+}
+
+fn change_date(change: &Change) -> Date {
+    match change {
+        Change::Day { date, .. } | Change::RemoveDay { date } => *date,
+        Change::Interval { start, .. } | Change::RemoveInterval { start } => start.date(),
+    }
+}
+
+// the calendar built from the shared logs, a device joining the sync first logs its existing data
+pub fn load(config: &Config, calendar: &HashMap<String, OneDaysWork>) -> Option<HashMap<String, OneDaysWork>> {
+    let sync_dir = sync_dir(config)?;
+    if !log_path(sync_dir, &config.device_name).exists() {
+        if let Err(error) = fs::create_dir_all(sync_dir).and_then(|_| append_changes(config, diff_calendars(&HashMap::new(), calendar))) {
+            println!("ERROR ::: could not write sync log to {}: {error}", sync_dir.display());
+            return None;
+        }
+    }
+    Some(merge_logs(read_logs(sync_dir), config.get_workday_span()))
+}
+
+// sends `Message::SyncLogsChanged` when any log in the sync directory changes, including our own
+pub fn sync_stream(sync_dir: PathBuf) -> impl Stream<Item = Message> {
+    iced::stream::channel(4, |mut output| async move {
+        thread::spawn(move || {
+            let stamp = || {
+                log_files(&sync_dir)
+                    .iter()
+                    .filter_map(|path| fs::metadata(path).ok())
+                    .map(|metadata| (metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH), metadata.len()))
+                    .collect::<Vec<_>>()
+            };
+            let mut last = stamp();
+            loop {
+                thread::sleep(POLL_INTERVAL);
+                let current = stamp();
+                if current != last {
+                    last = current;
+                    if block_on(output.send(Message::SyncLogsChanged)).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        std::future::pending::<()>().await
+    })
+}
@@ -0,0 +1,425 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use iced::futures::Stream;
+
+use crate::gui::gui_main::Message;
+use crate::i18n::{t, tf};
+use crate::utils::format_hours;
+
+// what the tray icon shows, see `App::tray_status`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrayStatus {
+    pub running: bool,
+    // worked today including the running interval
+    pub today_hours: f32,
+    // the running interval's label
+    pub project: Option<String>,
+    // the labels used so far, offered to switch to
+    pub projects: Vec<String>,
+}
+
+impl TrayStatus {
+    pub fn title(&self) -> String {
+        match (&self.project, self.running) {
+            (Some(project), true) => tf("{project} running, {hours} h today", &[("project", project), ("hours", &format_hours(self.today_hours))]),
+            _ => tf("Stopped, {hours} h today", &[("hours", &format_hours(self.today_hours))]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrayAction {
+    Start,
+    Stop,
+    SwitchProject(String),
+    Open,
+    Quit,
+}
+
+impl TrayAction {
+    pub fn message(&self) -> Message {
+        match self {
+            TrayAction::Start => Message::Start,
+            TrayAction::Stop => Message::Stop,
+            TrayAction::SwitchProject(project) => Message::SwitchProject(project.clone()),
+            TrayAction::Open => Message::Show,
+            TrayAction::Quit => Message::Quit,
+        }
+    }
+}
+
+// an entry of the tray menu, entries without action and label are separators
+#[derive(Debug, Clone, PartialEq)]
+pub struct MenuEntry {
+    pub id: i32,
+    pub label: String,
+    pub enabled: bool,
+    // Some for the radio buttons of the project menu
+    pub checked: Option<bool>,
+    pub action: Option<TrayAction>,
+    pub children: Vec<MenuEntry>,
+}
+
+impl MenuEntry {
+    fn new(id: i32, label: &str, action: TrayAction) -> MenuEntry {
+        MenuEntry {
+            id,
+            label: label.to_owned(),
+            enabled: true,
+            checked: None,
+            action: Some(action),
+            children: vec![],
+        }
+    }
+
+    fn separator(id: i32) -> MenuEntry {
+        MenuEntry {
+            id,
+            label: String::new(),
+            enabled: true,
+            checked: None,
+            action: None,
+            children: vec![],
+        }
+    }
+
+    pub fn is_separator(&self) -> bool {
+        self.label.is_empty() && self.action.is_none()
+    }
+
+    pub fn find(&self, id: i32) -> Option<&MenuEntry> {
+        if self.id == id {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(id))
+    }
+}
+
+// the projects get ids from 100 on, the menu is rebuilt with every status
+pub fn menu(status: &TrayStatus) -> MenuEntry {
+    let projects = status
+        .projects
+        .iter()
+        .enumerate()
+        .map(|(i, project)| MenuEntry {
+            checked: Some(status.running && status.project.as_ref() == Some(project)),
+            ..MenuEntry::new(100 + i as i32, project, TrayAction::SwitchProject(project.clone()))
+        })
+        .collect();
+    MenuEntry {
+        children: vec![
+            MenuEntry {
+                enabled: !status.running,
+                ..MenuEntry::new(1, t("Start"), TrayAction::Start)
+            },
+            MenuEntry {
+                enabled: status.running,
+                ..MenuEntry::new(2, t("Stop"), TrayAction::Stop)
+            },
+            MenuEntry {
+                action: None,
+                children: projects,
+                ..MenuEntry::new(3, t("Switch project"), TrayAction::Open)
+            },
+            MenuEntry::separator(4),
+            MenuEntry::new(5, t("Open window"), TrayAction::Open),
+            MenuEntry::new(6, t("Quit"), TrayAction::Quit),
+        ],
+        action: None,
+        ..MenuEntry::new(0, "Time Tracker", TrayAction::Open)
+    }
+}
+
+static STATUS: Mutex<Option<TrayStatus>> = Mutex::new(None);
+// set once a tray host took the icon, only then closing the window keeps the app running
+static SHOWN: AtomicBool = AtomicBool::new(false);
+
+pub fn is_shown() -> bool {
+    SHOWN.load(Ordering::Relaxed)
+}
+
+fn current_status() -> TrayStatus {
+    STATUS.lock().unwrap().clone().unwrap_or_default()
+}
+
+// hands the app's state to the tray icon, the host is told only about real changes
+pub fn update_tray(status: TrayStatus) {
+    let mut current = STATUS.lock().unwrap();
+    if current.as_ref() == Some(&status) {
+        return;
+    }
+    *current = Some(status);
+    drop(current);
+    #[cfg(target_os = "linux")]
+    sni::status_changed();
+}
+
+// shows the tray icon and sends the menu's messages, nothing on other systems or without a tray host
+pub fn tray_stream() -> impl Stream<Item = Message> {
+    iced::stream::channel(4, |output| async move {
+        thread::spawn(move || {
+            #[cfg(target_os = "linux")]
+            if let Err(error) = sni::register(output) {
+                println!("WARN ::: no tray icon: {error}");
+            }
+            #[cfg(not(target_os = "linux"))]
+            drop(output);
+        });
+        std::future::pending::<()>().await
+    })
+}
+
+// a StatusNotifierItem with a com.canonical.dbusmenu menu, as shown by KDE, GNOME's appindicator extension and most panels
+#[cfg(target_os = "linux")]
+mod sni {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::OnceLock;
+
+    use iced::futures::channel::mpsc::Sender;
+    use iced::futures::SinkExt;
+    use zbus::blocking::Connection;
+    use zbus::interface;
+    use zbus::zvariant::{OwnedObjectPath, StructureBuilder, Value};
+
+    use super::{current_status, menu, MenuEntry, TrayStatus, SHOWN};
+    use crate::gui::gui_main::Message;
+
+    const ITEM_PATH: &str = "/StatusNotifierItem";
+    const MENU_PATH: &str = "/MenuBar";
+    const ITEM: &str = "org.kde.StatusNotifierItem";
+    const MENU: &str = "com.canonical.dbusmenu";
+
+    // icon name, icon pixmaps as width, height and ARGB data, title and description
+    type ToolTip = (String, Vec<(i32, i32, Vec<u8>)>, String, String);
+    // id, properties and the children as variants of the same structure
+    type Layout = (i32, HashMap<String, Value<'static>>, Vec<Value<'static>>);
+
+    static CONNECTION: OnceLock<Connection> = OnceLock::new();
+    static REVISION: AtomicU32 = AtomicU32::new(1);
+
+    pub fn register(output: Sender<Message>) -> zbus::Result<()> {
+        let name = format!("org.kde.StatusNotifierItem-{}-1", std::process::id());
+        let connection = zbus::blocking::connection::Builder::session()?
+            .name(name.as_str())?
+            .serve_at(ITEM_PATH, Item { output: output.clone() })?
+            .serve_at(MENU_PATH, Menu { output })?
+            .build()?;
+        connection.call_method(
+            Some("org.kde.StatusNotifierWatcher"),
+            "/StatusNotifierWatcher",
+            Some("org.kde.StatusNotifierWatcher"),
+            "RegisterStatusNotifierItem",
+            &(name.as_str()),
+        )?;
+        // kept for the signals, dropping it would remove the icon
+        let _ = CONNECTION.set(connection);
+        SHOWN.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn status_changed() {
+        let Some(connection) = CONNECTION.get() else {
+            return;
+        };
+        let revision = REVISION.fetch_add(1, Ordering::Relaxed) + 1;
+        let signals = [
+            (ITEM_PATH, ITEM, "NewIcon"),
+            (ITEM_PATH, ITEM, "NewTitle"),
+            (ITEM_PATH, ITEM, "NewToolTip"),
+        ];
+        for (path, iface, signal) in signals {
+            if let Err(error) = connection.emit_signal(None::<&str>, path, iface, signal, &()) {
+                println!("WARN ::: could not update tray icon: {error}");
+                return;
+            }
+        }
+        let _ = connection.emit_signal(None::<&str>, MENU_PATH, MENU, "LayoutUpdated", &(revision, 0i32));
+    }
+
+    fn icon_name(status: &TrayStatus) -> &'static str {
+        match status.running {
+            true => "media-record",
+            false => "media-playback-pause",
+        }
+    }
+
+    struct Item {
+        output: Sender<Message>,
+    }
+
+    #[interface(name = "org.kde.StatusNotifierItem")]
+    impl Item {
+        // a left click opens the window
+        async fn activate(&self, _x: i32, _y: i32) {
+            let _ = self.output.clone().send(Message::Show).await;
+        }
+
+        // a middle click starts or stops
+        async fn secondary_activate(&self, _x: i32, _y: i32) {
+            let message = match current_status().running {
+                true => Message::Stop,
+                false => Message::Start,
+            };
+            let _ = self.output.clone().send(message).await;
+        }
+
+        fn context_menu(&self, _x: i32, _y: i32) {}
+
+        fn scroll(&self, _delta: i32, _orientation: &str) {}
+
+        #[zbus(property)]
+        fn category(&self) -> &str {
+            "ApplicationStatus"
+        }
+
+        #[zbus(property)]
+        fn id(&self) -> &str {
+            "time_tracker"
+        }
+
+        #[zbus(property)]
+        fn title(&self) -> String {
+            current_status().title()
+        }
+
+        #[zbus(property)]
+        fn status(&self) -> &str {
+            "Active"
+        }
+
+        #[zbus(property)]
+        fn icon_name(&self) -> &str {
+            icon_name(&current_status())
+        }
+
+        #[zbus(property)]
+        fn tool_tip(&self) -> ToolTip {
+            let status = current_status();
+            (icon_name(&status).to_owned(), vec![], "Time Tracker".to_owned(), status.title())
+        }
+
+        #[zbus(property)]
+        fn item_is_menu(&self) -> bool {
+            false
+        }
+
+        #[zbus(property)]
+        fn menu(&self) -> OwnedObjectPath {
+            OwnedObjectPath::try_from(MENU_PATH).unwrap()
+        }
+    }
+
+    struct Menu {
+        output: Sender<Message>,
+    }
+
+    fn properties(entry: &MenuEntry) -> HashMap<String, Value<'static>> {
+        let mut properties = HashMap::new();
+        if entry.is_separator() {
+            properties.insert("type".to_owned(), Value::from("separator"));
+            return properties;
+        }
+        properties.insert("label".to_owned(), Value::from(entry.label.clone()));
+        properties.insert("enabled".to_owned(), Value::from(entry.enabled));
+        if let Some(checked) = entry.checked {
+            properties.insert("toggle-type".to_owned(), Value::from("radio"));
+            properties.insert("toggle-state".to_owned(), Value::from(checked as i32));
+        }
+        if !entry.children.is_empty() {
+            properties.insert("children-display".to_owned(), Value::from("submenu"));
+        }
+        properties
+    }
+
+    // the `(ia{sv}av)` layout down to `depth`, -1 is all of it
+    fn layout(entry: &MenuEntry, depth: i32) -> Layout {
+        let children = match depth {
+            0 => vec![],
+            _ => entry
+                .children
+                .iter()
+                .map(|child| {
+                    let (id, properties, children) = layout(child, depth - 1);
+                    Value::from(StructureBuilder::new().add_field(id).add_field(properties).add_field(children).build())
+                })
+                .collect(),
+        };
+        (entry.id, properties(entry), children)
+    }
+
+    #[interface(name = "com.canonical.dbusmenu")]
+    impl Menu {
+        fn get_layout(
+            &self,
+            parent_id: i32,
+            recursion_depth: i32,
+            _property_names: Vec<String>,
+        ) -> (u32, Layout) {
+            let root = menu(&current_status());
+            let parent = root.find(parent_id).unwrap_or(&root);
+            (REVISION.load(Ordering::Relaxed), layout(parent, recursion_depth))
+        }
+
+        fn get_group_properties(&self, ids: Vec<i32>, _property_names: Vec<String>) -> Vec<(i32, HashMap<String, Value<'static>>)> {
+            let root = menu(&current_status());
+            ids.into_iter()
+                .filter_map(|id| root.find(id).map(|entry| (id, properties(entry))))
+                .collect()
+        }
+
+        fn get_property(&self, id: i32, name: &str) -> Value<'static> {
+            let root = menu(&current_status());
+            root.find(id)
+                .and_then(|entry| properties(entry).remove(name))
+                .unwrap_or(Value::from(""))
+        }
+
+        async fn event(&self, id: i32, event_id: &str, _data: Value<'_>, _timestamp: u32) {
+            if event_id != "clicked" {
+                return;
+            }
+            let action = menu(&current_status()).find(id).and_then(|entry| entry.action.clone());
+            if let Some(action) = action {
+                let _ = self.output.clone().send(action.message()).await;
+            }
+        }
+
+        async fn event_group(&self, events: Vec<(i32, String, Value<'_>, u32)>) -> Vec<i32> {
+            for (id, event_id, data, timestamp) in events {
+                self.event(id, &event_id, data, timestamp).await;
+            }
+            vec![]
+        }
+
+        fn about_to_show(&self, _id: i32) -> bool {
+            false
+        }
+
+        fn about_to_show_group(&self, _ids: Vec<i32>) -> (Vec<i32>, Vec<i32>) {
+            (vec![], vec![])
+        }
+
+        #[zbus(property)]
+        fn version(&self) -> u32 {
+            3
+        }
+
+        #[zbus(property)]
+        fn text_direction(&self) -> &str {
+            "ltr"
+        }
+
+        #[zbus(property)]
+        fn status(&self) -> &str {
+            "normal"
+        }
+
+        #[zbus(property)]
+        fn icon_theme_path(&self) -> Vec<String> {
+            vec![]
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use iced::futures::executor::block_on;
+use iced::futures::{SinkExt, Stream};
+use jiff::civil::Date;
+use serde::{Deserialize, Serialize};
+
+use crate::gui::gui_logic::OneDaysWork;
+use crate::gui::gui_main::Message;
+use crate::gui::serialize::data_path;
+
+// sync tools usually replace the whole file, polling the modification time catches that as well
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// days changed both in the app and in the file since the app last read or wrote it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReloadConflict {
+    pub dates: Vec<Date>,
+    // the file content, used if the user decides to take the file's version
+    pub remote: HashMap<String, OneDaysWork>,
+}
+
+pub struct MergeResult {
+    pub calendar: HashMap<String, OneDaysWork>,
+    // the local version was kept for these days
+    pub conflicts: Vec<Date>,
+}
+
+// sends `Message::DataFileChanged` whenever the data file's modification time or size changes,
+// our own writes are reported too and filtered by the app comparing the content
+pub fn watch_stream() -> impl Stream<Item = Message> {
+    iced::stream::channel(4, |mut output| async move {
+        thread::spawn(move || {
+            let path = data_path();
+            let stamp = || fs::metadata(&path).ok().map(|metadata| (metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH), metadata.len()));
+            let mut last = stamp();
+            loop {
+                thread::sleep(POLL_INTERVAL);
+                let current = stamp();
+                if current != last {
+                    last = current;
+                    if block_on(output.send(Message::DataFileChanged)).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        std::future::pending::<()>().await
+    })
+}
+
+// three way merge per day: `base` is what the app last read or wrote, `local` the app's calendar
+// and `remote` the file's new content. a day changed on one side only takes that side's version,
+// a day changed differently on both sides keeps the local version and is reported as a conflict
+pub fn merge_calendars(
+    base: &HashMap<String, OneDaysWork>,
+    local: &HashMap<String, OneDaysWork>,
+    remote: &HashMap<String, OneDaysWork>,
+) -> MergeResult {
+    let keys: BTreeSet<&String> = base.keys().chain(local.keys()).chain(remote.keys()).collect();
+    let mut result = MergeResult {
+        calendar: HashMap::new(),
+        conflicts: vec![],
+    };
+
+    for key in keys {
+        let (base_day, local_day, remote_day) = (base.get(key), local.get(key), remote.get(key));
+        let merged = if same_day(local_day, remote_day) || same_day(local_day, base_day) {
+            remote_day
+        } else if same_day(remote_day, base_day) {
+            local_day
+        } else {
+            if let Some(date) = local_day.or(remote_day).map(|work_day| work_day.date) {
+                result.conflicts.push(date);
+            }
+            local_day
+        };
+        if let Some(work_day) = merged {
+            result.calendar.insert(key.clone(), work_day.clone());
+        }
+    }
+    result
+}
+
+// spans have no usable equality, the serialized form is what ends up in the file anyway
+pub fn same_day(a: Option<&OneDaysWork>, b: Option<&OneDaysWork>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => serde_json::to_value(a).unwrap() == serde_json::to_value(b).unwrap(),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+pub fn same_calendar(a: &HashMap<String, OneDaysWork>, b: &HashMap<String, OneDaysWork>) -> bool {
+    a.len() == b.len() && a.iter().all(|(key, work_day)| same_day(Some(work_day), b.get(key)))
+}
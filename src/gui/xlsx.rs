@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use jiff::civil::Date;
+use jiff::{Unit, Zoned};
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook, Worksheet, XlsxError};
+
+use crate::config::Config;
+use crate::gui::gui_logic::OneDaysWork;
+use crate::gui::serialize::location_label;
+use crate::utils::{compute_should_hours, contingent_until, month_target_end, month_target_hours, span_in_hours};
+
+const MONTH_HEADINGS: [&str; 13] = [
+    "Date", "Weekday", "Location", "Absence", "Start", "End", "Duration", "Break", "Day total", "Contingent", "Correction",
+    "Day note", "Note",
+];
+const SUMMARY_HEADINGS: [&str; 6] = [
+    "Month", "Worked hours", "Target hours", "Difference", "Vacation days", "Contingent",
+];
+
+struct Formats {
+    heading: Format,
+    date: Format,
+    time: Format,
+    duration: Format,
+    hours: Format,
+    contingent: Format,
+}
+
+impl Formats {
+    fn new() -> Formats {
+        Formats {
+            heading: Format::new().set_bold(),
+            date: Format::new().set_num_format("yyyy-mm-dd"),
+            time: Format::new().set_num_format("hh:mm"),
+            duration: Format::new().set_num_format("[h]:mm"),
+            hours: Format::new().set_num_format("0.00"),
+            contingent: Format::new().set_num_format("+0.00;-0.00;0.00"),
+        }
+    }
+}
+
+// what a month sheet reports back to the summary sheet
+struct MonthResult {
+    last_row: u32,
+    target_hours: f32,
+    vacation_days: u32,
+    contingent: f32,
+}
+
+// one sheet per month plus a summary sheet in front, times are real excel times so totals can be recomputed in excel
+pub fn export_xlsx(path: &Path, config: &Config, work_days: Vec<OneDaysWork>) -> io::Result<()> {
+    write_workbook(path, config, work_days).map_err(|error| io::Error::other(error.to_string()))
+}
+
+fn write_workbook(path: &Path, config: &Config, work_days: Vec<OneDaysWork>) -> Result<(), XlsxError> {
+    let formats = Formats::new();
+
+    let mut months = BTreeMap::<(i16, i8), Vec<OneDaysWork>>::new();
+    for work_day in work_days {
+        months.entry((work_day.date.year(), work_day.date.month())).or_default().push(work_day);
+    }
+
+    let language = config.language;
+    let mut summary = Worksheet::new();
+    summary.set_name(language.tr("Summary"))?;
+    for (col, heading) in SUMMARY_HEADINGS.iter().enumerate() {
+        summary.write_string_with_format(0, col as u16, language.tr(heading), &formats.heading)?;
+    }
+
+    let mut month_sheets = vec![];
+    let mut total_worked: f32 = 0.;
+    for (year, month) in months.keys().cloned().collect::<Vec<_>>() {
+        let name = format!("{year}-{month:02}");
+        let mut sheet = Worksheet::new();
+        sheet.set_name(&name)?;
+        let result = write_month_sheet(&mut sheet, &months[&(year, month)], config, &mut total_worked, &formats)?;
+
+        let row = month_sheets.len() as u32 + 1;
+        let excel_row = row + 1;
+        summary.write_string(row, 0, &name)?;
+        summary.write_formula_with_format(row, 1, format!("=SUM('{name}'!I2:I{})*24", result.last_row).as_str(), &formats.hours)?;
+        summary.write_number_with_format(row, 2, result.target_hours, &formats.hours)?;
+        summary.write_formula_with_format(row, 3, format!("=B{excel_row}-C{excel_row}").as_str(), &formats.contingent)?;
+        summary.write_number(row, 4, result.vacation_days)?;
+        summary.write_number_with_format(row, 5, result.contingent, &formats.contingent)?;
+
+        month_sheets.push(sheet);
+    }
+
+    let total_row = month_sheets.len() as u32 + 1;
+    if total_row > 1 {
+        summary.write_string_with_format(total_row, 0, language.tr("Total"), &formats.heading)?;
+        summary.write_formula_with_format(total_row, 1, format!("=SUM(B2:B{total_row})").as_str(), &formats.hours)?;
+        summary.write_formula_with_format(total_row, 2, format!("=SUM(C2:C{total_row})").as_str(), &formats.hours)?;
+        summary.write_formula_with_format(total_row, 3, format!("=SUM(D2:D{total_row})").as_str(), &formats.contingent)?;
+        summary.write_formula(total_row, 4, format!("=SUM(E2:E{total_row})").as_str())?;
+    }
+    summary.set_column_width(0, 12)?;
+    for col in 1..SUMMARY_HEADINGS.len() as u16 {
+        summary.set_column_width(col, 14)?;
+    }
+
+    let mut workbook = Workbook::new();
+    workbook.push_worksheet(summary);
+    for sheet in month_sheets {
+        workbook.push_worksheet(sheet);
+    }
+    workbook.save(path)
+}
+
+fn write_month_sheet(
+    sheet: &mut Worksheet,
+    work_days: &[OneDaysWork],
+    config: &Config,
+    total_worked: &mut f32,
+    formats: &Formats,
+) -> Result<MonthResult, XlsxError> {
+    let language = config.language;
+    for (col, heading) in MONTH_HEADINGS.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, language.tr(heading), &formats.heading)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+
+    let mut row: u32 = 1;
+    let mut vacation_days = 0;
+    for work_day in work_days {
+        let date = work_day.date;
+        if let Some(sum_work) = work_day.sum_work {
+            *total_worked += span_in_hours(&sum_work);
+        }
+        let contingent = *total_worked - compute_should_hours(config.start_date, date, config);
+
+        sheet.write_datetime_with_format(row, 0, excel_date(date)?, &formats.date)?;
+        sheet.write_string(row, 1, language.weekday(date.weekday()))?;
+        sheet.write_string(row, 2, language.tr(location_label(work_day.location)))?;
+        if work_day.vacation {
+            vacation_days += 1;
+            sheet.write_string(row, 3, language.tr("Vacation"))?;
+        } else if work_day.added_workday {
+            sheet.write_string(row, 3, language.tr("Generic workday"))?;
+        }
+        if let Some(sum_work) = work_day.sum_work {
+            sheet.write_number_with_format(row, 8, span_in_hours(&sum_work) / 24., &formats.duration)?;
+        }
+        sheet.write_number_with_format(row, 9, contingent, &formats.contingent)?;
+        if !work_day.note.is_empty() {
+            sheet.write_string(row, 11, &work_day.note)?;
+        }
+
+        if work_day.work_duration.is_empty() {
+            row += 1;
+        }
+        for work_times in &work_day.work_duration {
+            if let Some(start) = &work_times.start {
+                sheet.write_number_with_format(row, 4, day_fraction(start.time()), &formats.time)?;
+            }
+            if let Some(end) = &work_times.end {
+                sheet.write_number_with_format(row, 5, day_fraction(end.time()), &formats.time)?;
+            }
+            if let Some(duration) = &work_times.duration {
+                sheet.write_number_with_format(row, 6, span_in_hours(duration) / 24., &formats.duration)?;
+            }
+            if let Some(pause) = &work_times.pause {
+                sheet.write_number_with_format(row, 7, span_in_hours(pause) / 24., &formats.duration)?;
+            }
+            if let Some(correction) = work_times.corrections.last() {
+                sheet.write_string(row, 10, &correction.reason)?;
+            }
+            if !work_times.note.is_empty() {
+                sheet.write_string(row, 12, &work_times.note)?;
+            }
+            row += 1;
+        }
+    }
+
+    let last_row = row;
+    sheet.write_string_with_format(row, 0, language.tr("Total"), &formats.heading)?;
+    for (col, letter) in [(6, "G"), (7, "H"), (8, "I")] {
+        sheet.write_formula_with_format(row, col, format!("=SUM({letter}2:{letter}{last_row})").as_str(), &formats.duration)?;
+    }
+    // the same target and contingent as the printable timesheet, see `month_target_hours`
+    let month = work_days[0].date;
+    let today = Zoned::now().date();
+    let target_hours = month_target_hours(month, today, config);
+    let contingent = contingent_until(*total_worked, month_target_end(month, today), config);
+    sheet.write_number_with_format(row, 9, contingent, &formats.contingent)?;
+
+    sheet.set_column_width(0, 12)?;
+    sheet.set_column_width(1, 11)?;
+    sheet.set_column_width(2, 12)?;
+    sheet.set_column_width(3, 16)?;
+    sheet.set_column_width(11, 30)?;
+    sheet.set_column_width(12, 30)?;
+
+    Ok(MonthResult {
+        last_row,
+        target_hours,
+        vacation_days,
+        contingent,
+    })
+}
+
+fn excel_date(date: Date) -> Result<ExcelDateTime, XlsxError> {
+    ExcelDateTime::from_ymd(date.year() as u16, date.month() as u8, date.day() as u8)
+}
+
+// excel stores times of day as the fraction of a day
+fn day_fraction(time: jiff::civil::Time) -> f64 {
+    let time = time.round(Unit::Minute).unwrap();
+    (time.hour() as f64 * 60. + time.minute() as f64) / (24. * 60.)
+}
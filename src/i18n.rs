@@ -0,0 +1,310 @@
+use std::env;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use jiff::civil::{Date, Weekday};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    // `en`, `de` or a locale like `de_DE.UTF-8`
+    pub fn parse(input: &str) -> Option<Language> {
+        let code = input.trim().to_lowercase();
+        match code.get(..2)? {
+            "en" => Some(Language::English),
+            "de" => Some(Language::German),
+            _ => None,
+        }
+    }
+
+    // the language of the user's locale, English if it isn't translated
+    pub fn from_env() -> Language {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Language::parse(&value))
+            .unwrap_or_default()
+    }
+
+    // the translation of an English text, the text itself if the catalog has none
+    pub fn tr(&self, text: &'static str) -> &'static str {
+        match self {
+            Language::English => text,
+            Language::German => german(text).unwrap_or(text),
+        }
+    }
+
+    // the translation with its `{name}` placeholders filled
+    pub fn tf(&self, text: &'static str, args: &[(&str, &dyn Display)]) -> String {
+        let mut text = self.tr(text).to_owned();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{name}}}"), &value.to_string());
+        }
+        text
+    }
+
+    // `2024-10-31` or `31.10.2024`
+    pub fn format_date(&self, date: Date) -> String {
+        match self {
+            Language::English => date.to_string(),
+            Language::German => date.strftime("%d.%m.%Y").to_string(),
+        }
+    }
+
+    pub fn weekday(&self, weekday: Weekday) -> &'static str {
+        let (english, german) = match weekday {
+            Weekday::Monday => ("Monday", "Montag"),
+            Weekday::Tuesday => ("Tuesday", "Dienstag"),
+            Weekday::Wednesday => ("Wednesday", "Mittwoch"),
+            Weekday::Thursday => ("Thursday", "Donnerstag"),
+            Weekday::Friday => ("Friday", "Freitag"),
+            Weekday::Saturday => ("Saturday", "Samstag"),
+            Weekday::Sunday => ("Sunday", "Sonntag"),
+        };
+        match self {
+            Language::English => english,
+            Language::German => german,
+        }
+    }
+
+    // `Thu 2024-10-31` or `Do 31.10.2024`
+    pub fn format_day(&self, date: Date) -> String {
+        let weekday = self.weekday(date.weekday());
+        let short = match self {
+            Language::English => &weekday[..3],
+            Language::German => &weekday[..2],
+        };
+        format!("{short} {}", self.format_date(date))
+    }
+
+    // a decimal number with the locale's separator, `7.50` or `7,50`
+    pub fn decimal(&self, value: f32, digits: usize) -> String {
+        let number = format!("{value:.digits$}");
+        match self {
+            Language::English => number,
+            Language::German => number.replace('.', ","),
+        }
+    }
+}
+
+impl Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Language::English => "English",
+                Language::German => "Deutsch",
+            }
+        )
+    }
+}
+
+// the language of the ui, set from the config at startup. exports take it from the config directly
+static LANGUAGE: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_language(language: Language) {
+    LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+pub fn language() -> Language {
+    match LANGUAGE.load(Ordering::Relaxed) {
+        1 => Language::German,
+        _ => Language::English,
+    }
+}
+
+// translates a ui text into the current language
+pub fn t(text: &'static str) -> &'static str {
+    language().tr(text)
+}
+
+// translates a ui text and fills its `{name}` placeholders
+pub fn tf(text: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    language().tf(text, args)
+}
+
+pub fn format_date(date: Date) -> String {
+    language().format_date(date)
+}
+
+pub fn format_day(date: Date) -> String {
+    language().format_day(date)
+}
+
+// a text as written by any of the languages, for reading files exported in another language
+pub fn in_any_language(input: &str, text: &'static str) -> bool {
+    Language::ALL.iter().any(|language| language.tr(text).eq_ignore_ascii_case(input.trim()))
+}
+
+fn german(text: &str) -> Option<&'static str> {
+    Some(match text {
+        // main window
+        "Start" => "Start",
+        "Stop" => "Stopp",
+        "Focus" => "Fokus",
+        "Date" => "Datum",
+        "today" => "heute",
+        "search" => "suchen",
+        "corrections" => "Korrekturen",
+        "import" => "Import",
+        "export" => "Export",
+        "Vacation" => "Urlaub",
+        "Generic Workday" => "Pauschaler Arbeitstag",
+        "note for this day" => "Notiz zu diesem Tag",
+        "Duration" => "Dauer",
+        "Break" => "Pause",
+        "Note" => "Notiz",
+        "Contingent: " => "Stundenkonto: ",
+        "Focus blocks: {count}" => "Fokusblöcke: {count}",
+        "mini" => "Mini",
+        "full" => "Voll",
+        "home-office" => "Homeoffice",
+        "office" => "Büro",
+        // banners
+        "The data file was changed elsewhere, conflicting edits on {dates}" => "Die Datendatei wurde woanders geändert, widersprüchliche Änderungen am {dates}",
+        "keep mine" => "meine behalten",
+        "use file" => "Datei übernehmen",
+        "You were away from {from} to {to} ({minutes} min)" => "Du warst von {from} bis {to} weg ({minutes} min)",
+        "keep" => "behalten",
+        "break" => "Pause",
+        "discard and stop" => "verwerfen und stoppen",
+        "Focus until {time}" => "Fokus bis {time}",
+        "Break until {time}" => "Pause bis {time}",
+        "next focus block" => "nächster Fokusblock",
+        "end focus" => "Fokus beenden",
+        "Focus block done" => "Fokusblock beendet",
+        "Take a {minutes} minute break." => "Mach {minutes} Minuten Pause.",
+        "Break is over" => "Pause vorbei",
+        "Start the next {minutes} minute focus block when you're back." => "Starte den nächsten Fokusblock mit {minutes} Minuten, wenn du zurück bist.",
+        // command palette
+        "type a command, enter runs the first" => "Befehl eingeben, Enter führt den ersten aus",
+        "close" => "schließen",
+        "End focus" => "Fokus beenden",
+        "Start focus block" => "Fokusblock starten",
+        "Previous day" => "Vorheriger Tag",
+        "Next day" => "Nächster Tag",
+        "Today" => "Heute",
+        "Toggle vacation" => "Urlaub umschalten",
+        "Toggle generic workday" => "Pauschalen Arbeitstag umschalten",
+        "Location: home office" => "Ort: Homeoffice",
+        "Location: office" => "Ort: Büro",
+        "Search" => "Suche",
+        "Export" => "Exportieren",
+        "Export corrections" => "Korrekturen exportieren",
+        "Import" => "Importieren",
+        "Undo" => "Rückgängig",
+        "Redo" => "Wiederholen",
+        "Quit" => "Beenden",
+        "Compact mode" => "Kompaktmodus",
+        "Full window" => "Volles Fenster",
+        "Theme" => "Design",
+        "Space" => "Leertaste",
+        // unlock
+        "Choose a passphrase to encrypt your work times" => "Wähle eine Passphrase, um deine Arbeitszeiten zu verschlüsseln",
+        "Enter the passphrase of your work times" => "Gib die Passphrase deiner Arbeitszeiten ein",
+        "passphrase" => "Passphrase",
+        "repeat the passphrase" => "Passphrase wiederholen",
+        "unlock" => "entsperren",
+        "the passphrases differ" => "die Passphrasen unterscheiden sich",
+        // dangling intervals and corrections
+        "The interval started on {date} at {time} was never stopped" => "Das Intervall vom {date} um {time} wurde nie gestoppt",
+        "End" => "Ende",
+        "suggested {time}" => "vorgeschlagen {time}",
+        "{count} more open intervals" => "{count} weitere offene Intervalle",
+        "discard interval" => "Intervall verwerfen",
+        "Edit interval on {date}" => "Intervall am {date} bearbeiten",
+        "Reason" => "Grund",
+        "why the recorded times were wrong" => "warum die erfassten Zeiten falsch waren",
+        "what was done" => "was gemacht wurde",
+        "cancel" => "abbrechen",
+        "save" => "speichern",
+        // search
+        "text in labels and notes" => "Text in Projekten und Notizen",
+        "project" => "Projekt",
+        "location" => "Ort",
+        "absence" => "Abwesenheit",
+        "from" => "von",
+        "to" => "bis",
+        "vacation" => "Urlaub",
+        "generic workday" => "pauschaler Arbeitstag",
+        "no absence" => "keine Abwesenheit",
+        "{intervals} intervals on {days} days, {hours} h" => "{intervals} Intervalle an {days} Tagen, {hours} h",
+        "clear" => "zurücksetzen",
+        // import
+        "Import {source} ({format})" => "Import {source} ({format})",
+        "{intervals} intervals on {days} days, {conflicts} days overlap existing entries and need replace" => "{intervals} Intervalle an {days} Tagen, {conflicts} Tage überlappen vorhandene Einträge und müssen ersetzt werden",
+        ", {count} events skipped (all-day or unreadable times)" => ", {count} Termine übersprungen (ganztägig oder unlesbare Zeiten)",
+        "{count} intervals" => "{count} Intervalle",
+        ", vacation" => ", Urlaub",
+        "! {existing} existing, {overlapping} overlapping" => "! {existing} vorhanden, {overlapping} überlappend",
+        "{existing} existing" => "{existing} vorhanden",
+        "replace" => "ersetzen",
+        "merge" => "zusammenführen",
+        // notifications and tray
+        "Not clocked in yet" => "Noch nicht eingestempelt",
+        "It's a workday and no work was recorded today." => "Heute ist ein Arbeitstag und es wurde noch keine Arbeit erfasst.",
+        "Daily target reached" => "Tagessoll erreicht",
+        "You worked {hours} h today." => "Du hast heute {hours} h gearbeitet.",
+        "Maximum working time reached" => "Höchstarbeitszeit erreicht",
+        "You worked {hours} h today, time to stop." => "Du hast heute {hours} h gearbeitet, Zeit aufzuhören.",
+        "Break due" => "Pause fällig",
+        "You worked {hours} h without a 30 minute break." => "Du hast {hours} h ohne 30 Minuten Pause gearbeitet.",
+        "Timer still running" => "Zeiterfassung läuft noch",
+        "Did you forget to stop?" => "Vergessen zu stoppen?",
+        "{project} running, {hours} h today" => "{project} läuft, heute {hours} h",
+        "Stopped, {hours} h today" => "Gestoppt, heute {hours} h",
+        "Switch project" => "Projekt wechseln",
+        "Open window" => "Fenster öffnen",
+        // csv export
+        "SUM WORK" => "SUMME ARBEIT",
+        "SUM BREAKS" => "SUMME PAUSEN",
+        "CONTINGENT" => "STUNDENKONTO",
+        "HOURS" => "STUNDEN",
+        "VACATION" => "URLAUB",
+        "START" => "BEGINN",
+        "END" => "ENDE",
+        "DURATION" => "DAUER",
+        "BREAK" => "PAUSE",
+        "NOTE" => "NOTIZ",
+        "CORRECTED" => "KORRIGIERT",
+        "Home office" => "Homeoffice",
+        "In office" => "Im Büro",
+        // spreadsheet and timesheet
+        "Weekday" => "Wochentag",
+        "Location" => "Ort",
+        "Absence" => "Abwesenheit",
+        "Day total" => "Tagessumme",
+        "Contingent" => "Stundenkonto",
+        "Correction" => "Korrektur",
+        "Day note" => "Tagesnotiz",
+        "Month" => "Monat",
+        "Worked hours" => "Gearbeitete Stunden",
+        "Target hours" => "Sollstunden",
+        "Difference" => "Differenz",
+        "Vacation days" => "Urlaubstage",
+        "Summary" => "Übersicht",
+        "Total" => "Summe",
+        "Generic workday" => "Pauschaler Arbeitstag",
+        "Worked" => "Gearbeitet",
+        "Breaks" => "Pausen",
+        "Target" => "Soll",
+        "Contingent at end of period" => "Stundenkonto am Ende des Zeitraums",
+        "Timesheet" => "Stundenzettel",
+        "Employee: {name}" => "Mitarbeiter: {name}",
+        "Period: {period}" => "Zeitraum: {period}",
+        "* corrected after recording, see the corrections report" => "* nach der Erfassung korrigiert, siehe Korrekturbericht",
+        "Date, signature employee" => "Datum, Unterschrift Mitarbeiter",
+        "Date, signature supervisor" => "Datum, Unterschrift Vorgesetzte",
+        _ => return None,
+    })
+}
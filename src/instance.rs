@@ -0,0 +1,209 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use iced::futures::Stream;
+
+use crate::gui::api::ApiRequest;
+use crate::gui::gui_main::Message;
+use crate::gui::serialize::data_path;
+
+// commands a second invocation forwards to the running instance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ControlCommand {
+    Start,
+    Stop,
+    Status,
+    Show,
+}
+
+impl ControlCommand {
+    pub fn parse(input: &str) -> Option<ControlCommand> {
+        match input.trim() {
+            "start" => Some(ControlCommand::Start),
+            "stop" => Some(ControlCommand::Stop),
+            "status" => Some(ControlCommand::Status),
+            "show" => Some(ControlCommand::Show),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ControlCommand::Start => "start",
+            ControlCommand::Stop => "stop",
+            ControlCommand::Status => "status",
+            ControlCommand::Show => "show",
+        }
+    }
+
+    // the api request doing the same, `show` only concerns the window
+    pub fn api_request(&self) -> Option<ApiRequest> {
+        let (method, path) = match self {
+            ControlCommand::Start => ("POST", "/start"),
+            ControlCommand::Stop => ("POST", "/stop"),
+            ControlCommand::Status => ("GET", "/status"),
+            ControlCommand::Show => return None,
+        };
+        Some(ApiRequest {
+            method: method.to_owned(),
+            path: path.to_owned(),
+            query: Default::default(),
+        })
+    }
+}
+
+// the socket lives next to the data file, so every data file has at most one running instance
+pub fn socket_path() -> PathBuf {
+    data_path().with_extension("sock")
+}
+
+// held by the instance that owns the data file, the operating system drops it when that process ends
+pub fn lock_path() -> PathBuf {
+    data_path().with_extension("lock")
+}
+
+// None if another process holds the lock
+pub fn try_lock(path: &Path) -> io::Result<Option<File>> {
+    let file = OpenOptions::new().create(true).truncate(false).write(true).open(path)?;
+    match file.try_lock() {
+        Ok(()) => Ok(Some(file)),
+        Err(TryLockError::WouldBlock) => Ok(None),
+        Err(TryLockError::Error(error)) => Err(error),
+    }
+}
+
+pub enum Instance {
+    // we are the only instance, the control socket is ready for `control_stream`
+    Primary,
+    // another instance already owns the data file
+    AlreadyRunning,
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::fs::File;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::sync::Mutex;
+    use std::thread;
+    use std::time::Duration;
+    use std::{fs, io};
+
+    use iced::futures::channel::mpsc as async_mpsc;
+    use iced::futures::executor::block_on;
+    use iced::futures::SinkExt;
+
+    use super::{lock_path, socket_path, try_lock, ControlCommand, Instance};
+    use crate::gui::api::{dispatch, ApiResponse};
+    use crate::gui::gui_main::Message;
+
+    static LISTENER: Mutex<Option<UnixListener>> = Mutex::new(None);
+    static LOCK: Mutex<Option<File>> = Mutex::new(None);
+
+    // the lock decides which instance owns the data file, not the socket. two instances starting
+    // together could both find the socket dead, both remove it and both bind their own
+    pub fn acquire() -> Instance {
+        match try_lock(&lock_path()) {
+            Ok(Some(lock)) => *LOCK.lock().unwrap() = Some(lock),
+            Ok(None) => return Instance::AlreadyRunning,
+            Err(error) => println!("WARN ::: could not lock {}: {error}", lock_path().display()),
+        }
+
+        // a socket still there is left over from an instance that didn't shut down cleanly
+        let path = socket_path();
+        let _ = fs::remove_file(&path);
+        match UnixListener::bind(&path) {
+            Ok(listener) => *LISTENER.lock().unwrap() = Some(listener),
+            Err(error) => println!("WARN ::: control socket {} not available: {error}", path.display()),
+        }
+        Instance::Primary
+    }
+
+    pub fn release() {
+        if LISTENER.lock().unwrap().take().is_some() {
+            let _ = fs::remove_file(socket_path());
+        }
+        LOCK.lock().unwrap().take();
+    }
+
+    pub fn send(command: ControlCommand) -> io::Result<String> {
+        let mut stream = UnixStream::connect(socket_path())?;
+        stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+        writeln!(stream, "{}", command.name())?;
+        let mut response = vec![];
+        stream.read_to_end(&mut response)?;
+        Ok(String::from_utf8_lossy(&response).trim_end().to_owned())
+    }
+
+    pub fn serve(output: async_mpsc::Sender<Message>) {
+        let Some(listener) = LISTENER.lock().unwrap().take() else {
+            return;
+        };
+        // put it back so `release` removes the socket file on exit
+        let accepting = listener.try_clone();
+        *LISTENER.lock().unwrap() = Some(listener);
+        let Ok(listener) = accepting else {
+            return;
+        };
+
+        for stream in listener.incoming().flatten() {
+            let mut output = output.clone();
+            thread::spawn(move || handle_connection(stream, &mut output));
+        }
+    }
+
+    fn handle_connection(stream: UnixStream, output: &mut async_mpsc::Sender<Message>) {
+        let mut line = String::new();
+        if BufReader::new(&stream).read_line(&mut line).is_err() {
+            return;
+        }
+        let response = match ControlCommand::parse(&line) {
+            Some(command) => match command.api_request() {
+                Some(request) => dispatch(output, request),
+                None => {
+                    let _ = block_on(output.send(Message::Show));
+                    ApiResponse::ok(serde_json::json!({ "shown": true }))
+                }
+            },
+            None => ApiResponse::error(400, "unknown command"),
+        };
+        let _ = writeln!(&stream, "{}", serde_json::to_string(&response.body).unwrap());
+    }
+}
+
+#[cfg(unix)]
+pub use unix::{acquire, release, send};
+
+#[cfg(not(unix))]
+pub fn acquire() -> Instance {
+    Instance::Primary
+}
+
+#[cfg(not(unix))]
+pub fn release() {}
+
+#[cfg(not(unix))]
+pub fn send(_command: ControlCommand) -> std::io::Result<String> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "no control socket on this platform"))
+}
+
+// forwards commands from the control socket into the app, does nothing if `acquire` didn't bind the socket
+pub fn control_stream() -> impl Stream<Item = Message> {
+    iced::stream::channel(16, |output| async move {
+        #[cfg(unix)]
+        std::thread::spawn(move || unix::serve(output));
+        #[cfg(not(unix))]
+        drop(output);
+        std::future::pending::<()>().await
+    })
+}
+
+// makes sure the socket file is removed however the gui ends
+pub struct ReleaseOnDrop;
+
+impl Drop for ReleaseOnDrop {
+    fn drop(&mut self) {
+        release();
+    }
+}
@@ -2,22 +2,74 @@
 
 mod gui {
     pub(crate) mod gui_main;
-    mod gui_logic;
-    mod serialize;
+    pub(crate) mod gui_logic;
+    pub(crate) mod serialize;
+    pub(crate) mod report;
+    pub(crate) mod range_report;
+    pub(crate) mod ical;
+    pub(crate) mod import;
+    pub(crate) mod csv_import;
+    pub(crate) mod api;
+    pub(crate) mod xlsx;
+    pub(crate) mod watch;
+    pub(crate) mod sync;
+    pub(crate) mod journal;
+    pub(crate) mod corrections;
+    pub(crate) mod crypto;
+    pub(crate) mod search;
+    pub(crate) mod idle;
+    pub(crate) mod session;
+    pub(crate) mod recover;
+    pub(crate) mod notify;
+    pub(crate) mod tray;
+    pub(crate) mod focus;
+    pub(crate) mod palette;
+    pub(crate) mod appearance;
 }
+mod cli;
 mod config;
+mod i18n;
+mod instance;
 mod utils;
 mod tests;
 
+use std::{env, process};
+
+use crate::cli::Command;
+use crate::config::Config;
+use crate::gui::appearance::{initial_position, initial_size};
 use crate::gui::gui_main::App;
+use crate::instance::{ControlCommand, Instance, ReleaseOnDrop};
 
-use iced:: Size;
 use iced_aw::iced_fonts;
 
 fn  main() -> iced::Result {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match cli::parse_args(&args) {
+        Ok(None) | Ok(Some(Command::Control(ControlCommand::Show))) => {}
+        Ok(Some(command)) => process::exit(cli::run(command)),
+        Err(message) => {
+            eprintln!("{message}");
+            process::exit(2);
+        }
+    }
+
+    // a second window would keep its own copy of the calendar and overwrite the first one's changes
+    if let Instance::AlreadyRunning = instance::acquire() {
+        let _ = instance::send(ControlCommand::Show);
+        return Ok(());
+    }
+    let _release = ReleaseOnDrop;
+
+    // the window opens where and as large as it was closed
+    let config = Config::get_config();
     iced::application("Time Tracker", App::update, App::view)
         .theme(App::theme)
+        .subscription(App::subscription)
         .font(iced_fonts::REQUIRED_FONT_BYTES)
-        .window_size(Size::new(500., 300.))
+        .window_size(initial_size(&config))
+        .position(initial_position(&config))
+        // closing only hides the window while the tray icon is shown, see `close_window`
+        .exit_on_close_request(false)
         .run_with(App::new)
-}
\ No newline at end of file
+}
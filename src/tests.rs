@@ -1,9 +1,39 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     use jiff::Span;
     use jiff::civil::Date;
+    use std::path::PathBuf;
+    use tempfile::{tempdir, TempDir};
+    use crate::cli::{parse_args, Command};
     use crate::config::Config;
-    use crate::utils::get_num_workdays;
+    use crate::gui::api::{forbidden, handle_request, parse_request};
+    use crate::gui::gui_main::{shortcut, App, Location, Message, State};
+    use crate::i18n::Language;
+    use crate::gui::appearance::{full_size, initial_size, ThemeChoice, COMPACT_WINDOW_SIZE};
+    use crate::gui::serialize::{serialize_calendar_to_csv, DateRange};
+    use crate::instance::{try_lock, ControlCommand};
+    use crate::gui::gui_logic::{OneDaysWork, WorkTimes};
+    use crate::gui::ical::{parse_ics, ImportedInterval, parse_location, write_ics};
+    use crate::gui::corrections::{collect_corrections, parse_corrected_time, write_corrections_csv};
+    use crate::gui::crypto::{is_encrypted, DataKey};
+    use crate::gui::csv_import::{parse_csv, ImportFormat, ImportedData};
+    use crate::gui::import::{apply_import, build_preview, ImportMode};
+    use crate::gui::search::{search, Absence, SearchForm, SearchQuery};
+    use crate::gui::idle::{parse_idle_hint, IdleDetector, IdlePeriod, IdleSource};
+    use crate::gui::session::{session_action, SessionAction, SessionEvent};
+    use crate::gui::recover::{find_dangling, RecoverForm};
+    use crate::gui::notify::{due_reminders, Reminder, SentReminders};
+    use crate::gui::tray::{menu, TrayAction};
+    use crate::gui::focus::FocusPhase;
+    use crate::gui::palette::{commands, filter};
+    use crate::gui::range_report::{build_range_report, write_json_report, write_markdown_summary, RangeReport};
+    use crate::gui::report::{export_report, ReportFormat};
+    use crate::gui::journal::{journal_path, read_journal, rebuild, Journal};
+    use crate::gui::sync::{append_changes, diff_calendars, merge_logs, read_logs};
+    use crate::gui::watch::{merge_calendars, same_day};
+    use crate::gui::xlsx::export_xlsx;
+    use crate::utils::{get_num_workdays, span_in_hours};
 
     #[test]
     fn test_spans() {
@@ -25,6 +55,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::zero_prefixed_literal)]
     fn test_get_work_days() {
         let today = Date::new(2024, 11, 01).unwrap();
 
@@ -58,4 +89,1382 @@ mod tests {
         let to =  Date::new(2024, 11, 2).unwrap();
         assert_eq!(get_num_workdays(from, to), 1.);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_export_args() {
+        let args: Vec<String> = vec![];
+        assert_eq!(parse_args(&args), Ok(None));
+
+        let args = vec!["--export".to_owned()];
+        assert_eq!(parse_args(&args), Ok(Some(Command::Export { path: None, month: None, range: DateRange::default() })));
+
+        let args = vec!["--export".to_owned(), "/tmp/out.csv".to_owned()];
+        assert_eq!(parse_args(&args), Ok(Some(Command::Export { path: Some(PathBuf::from("/tmp/out.csv")), month: None, range: DateRange::default() })));
+
+        let args = vec!["--export".to_owned(), "/tmp/out.pdf".to_owned(), "--month".to_owned(), "2024-11".to_owned()];
+        assert_eq!(
+            parse_args(&args),
+            Ok(Some(Command::Export {
+                path: Some(PathBuf::from("/tmp/out.pdf")),
+                month: Some(Date::new(2024, 11, 1).unwrap()),
+                range: DateRange::default(),
+            }))
+        );
+
+        let args: Vec<String> = ["--export", "out.json", "--from", "2024-11-01", "--to", "2024-11-15"].map(String::from).to_vec();
+        let range = DateRange { from: Some(Date::new(2024, 11, 1).unwrap()), to: Some(Date::new(2024, 11, 15).unwrap()) };
+        assert_eq!(parse_args(&args), Ok(Some(Command::Export { path: Some(PathBuf::from("out.json")), month: None, range })));
+
+        let args = vec!["--export".to_owned(), "--month".to_owned(), "11/2024".to_owned()];
+        assert!(parse_args(&args).is_err());
+
+        let args = vec!["--foo".to_owned()];
+        assert!(parse_args(&args).is_err());
+
+        let args = vec!["stop".to_owned()];
+        assert_eq!(parse_args(&args), Ok(Some(Command::Control(ControlCommand::Stop))));
+        let args = vec!["stop".to_owned(), "now".to_owned()];
+        assert!(parse_args(&args).is_err());
+
+        // only one instance gets the lock on the data file until it ends
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(".work_data.lock");
+        let first = try_lock(&path).unwrap();
+        assert!(first.is_some());
+        assert!(try_lock(&path).unwrap().is_none());
+        drop(first);
+        assert!(try_lock(&path).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_export_file_pattern() {
+        let config = Config {
+            export_file_pattern: "times_{date}.csv".to_owned(),
+            ..Default::default()
+        };
+        let today = jiff::Zoned::now().date();
+        assert_eq!(config.get_export_file_name(), format!("times_{today}.csv"));
+
+        // remembering an export changes only its own keys in the file on disk
+        let config = Config { export_dir: Some(PathBuf::from("/home/jane/exports")), ..config };
+        let on_disk = "hours_week = 40\n# written by hand\nexport_dir = /tmp\nlanguage = de\ntheme = Nord";
+        assert_eq!(
+            config.with_export_path(on_disk),
+            "hours_week = 40\n# written by hand\nlanguage = de\ntheme = Nord\nexport_dir = /home/jane/exports\nexport_file_pattern = times_{date}.csv"
+        );
+    }
+
+    fn sample_config() -> Config {
+        Config {
+            hours_week: 40.,
+            start_date: Date::new(2024, 10, 28).unwrap(),
+            employee_name: "Jane <Doe>".to_owned(),
+            ..Default::default()
+        }
+    }
+
+    fn sample_work_days(config: &Config) -> Vec<OneDaysWork> {
+        let start: jiff::Zoned = "2024-10-31T08:00[Europe/Berlin]".parse().unwrap();
+        let end: jiff::Zoned = "2024-10-31T16:30[Europe/Berlin]".parse().unwrap();
+        let mut work_day = OneDaysWork::init(start.date());
+        work_day.work_duration.push(WorkTimes {
+            duration: Some(start.until(&end).unwrap()),
+            start: Some(start),
+            end: Some(end),
+            ..WorkTimes::init()
+        });
+        work_day.sum_durations();
+        let mut vacation_day = OneDaysWork::init(Date::new(2024, 11, 4).unwrap());
+        vacation_day.vacation = true;
+        vacation_day.sum_work = Some(config.get_workday_span());
+
+        vec![work_day, vacation_day]
+    }
+
+    #[test]
+    fn test_export_xlsx() {
+        let config = sample_config();
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("export.xlsx");
+        export_xlsx(&path, &config, sample_work_days(&config)).unwrap();
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let mut read = |name: &str| {
+            let mut content = String::new();
+            std::io::Read::read_to_string(&mut archive.by_name(name).unwrap(), &mut content).unwrap();
+            content
+        };
+        let workbook = read("xl/workbook.xml");
+        let strings = read("xl/sharedStrings.xml");
+        let summary = read("xl/worksheets/sheet1.xml");
+        let october = read("xl/worksheets/sheet2.xml");
+        assert!(workbook.contains(r#"<sheet name="Summary" sheetId="1""#));
+        assert!(workbook.contains(r#"<sheet name="2024-10" sheetId="2""#));
+        assert!(workbook.contains(r#"<sheet name="2024-11" sheetId="3""#));
+
+        // one row per interval, times as fractions of a day and the running contingent
+        assert!(october.contains(r#"<c r="A2" s="4"><v>45596</v></c>"#));
+        assert!(strings.contains("<t>Thursday</t>"));
+        assert!(october.contains(r#"<c r="E2" s="6"><v>0.3333333333333333</v></c><c r="F2" s="6"><v>0.6875</v></c>"#));
+        assert!(october.contains(r#"<c r="J2" s="3"><v>-23.5</v></c>"#));
+
+        // the targets cover whole months, november has only a vacation day recorded
+        assert!(summary.contains(r#"<c r="C2" s="2"><v>32</v></c>"#));
+        assert!(summary.contains(r#"<c r="F2" s="3"><v>-23.5</v></c>"#));
+        assert!(summary.contains(r#"<c r="C3" s="2"><v>168</v></c>"#));
+        assert!(summary.contains(r#"<c r="E3"><v>1</v></c>"#));
+        assert!(summary.contains(r#"<c r="F3" s="3"><v>-183.5</v></c>"#));
+
+        // the headings follow the language like the weekday names
+        let german = Config { language: Language::German, ..sample_config() };
+        export_xlsx(&path, &german, sample_work_days(&german)).unwrap();
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let mut read = |name: &str| {
+            let mut content = String::new();
+            std::io::Read::read_to_string(&mut archive.by_name(name).unwrap(), &mut content).unwrap();
+            content
+        };
+        assert!(read("xl/workbook.xml").contains(r#"<sheet name="Übersicht" sheetId="1""#));
+        let strings = read("xl/sharedStrings.xml");
+        for german in ["Wochentag", "Tagessumme", "Sollstunden", "Donnerstag", "Homeoffice", "Urlaub", "Summe"] {
+            assert!(strings.contains(&format!("<t>{german}</t>")), "{german}");
+        }
+        for english in ["Weekday", "Thursday", "Vacation", "Total"] {
+            assert!(!strings.contains(&format!("<t>{english}</t>")), "{english}");
+        }
+    }
+
+    #[test]
+    fn test_export_report() {
+        let config = sample_config();
+        let month = Date::new(2024, 10, 1).unwrap();
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("report.html");
+        export_report(&path, &config, sample_work_days(&config), month, ReportFormat::Html).unwrap();
+        let html = std::fs::read_to_string(&path).unwrap();
+        assert!(html.contains("Jane &lt;Doe&gt;"));
+        assert!(html.contains("2024-10-01 - 2024-10-31"));
+        assert!(html.contains("<td>08:00</td><td>16:30</td><td>8:30</td>"));
+        assert!(!html.contains("2024-11-04"));
+        assert!(html.contains("signature supervisor"));
+        assert!(html.contains("<tr><th>Target</th><td>32:00</td></tr>"));
+
+        // the target covers the whole month, not just the days up to the last record
+        export_report(&path, &config, sample_work_days(&config), Date::new(2024, 11, 1).unwrap(), ReportFormat::Html).unwrap();
+        let html = std::fs::read_to_string(&path).unwrap();
+        assert!(html.contains("<tr><th>Target</th><td>168:00</td></tr>"));
+        assert!(html.contains("<tr><th>Contingent at end of period</th><td>- 183:30</td></tr>"));
+
+        // headings, labels and weekday names follow the language
+        let german = Config { language: Language::German, ..sample_config() };
+        export_report(&path, &german, sample_work_days(&german), month, ReportFormat::Html).unwrap();
+        let html = std::fs::read_to_string(&path).unwrap();
+        assert!(html.contains("<h1>Stundenzettel</h1>"));
+        assert!(html.contains("Mitarbeiter: Jane &lt;Doe&gt;<br>Zeitraum: 2024-10-01 - 2024-10-31"));
+        assert!(html.contains("<th>Datum</th><th>Wochentag</th><th>Ort</th>"));
+        assert!(html.contains("<td>Donnerstag</td><td>Homeoffice</td>"), "{html}");
+        assert!(html.contains("<tr><th>Soll</th><td>32:00</td></tr>"));
+        assert!(html.contains("Datum, Unterschrift Vorgesetzte"));
+        for english in ["Thursday", "Timesheet", "Weekday", "Target", "signature employee"] {
+            assert!(!html.contains(english), "{english}");
+        }
+
+        let path = dir.path().join("report.pdf");
+        export_report(&path, &config, sample_work_days(&config), month, ReportFormat::Pdf).unwrap();
+        let pdf = std::fs::read(&path).unwrap();
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+
+        // windows-1252 signs and long notes wrapped below the row
+        let mut work_days = sample_work_days(&config);
+        work_days[0].work_duration[0].note = format!("Hotel 120 € – {}", "review of the quarterly figures ".repeat(6));
+        export_report(&path, &config, work_days, month, ReportFormat::Pdf).unwrap();
+        let pdf = std::fs::read(&path).unwrap();
+        assert!(pdf.windows(9).any(|bytes| bytes == b"120 \x80 \x96 r"));
+        let note_lines = pdf.windows(7).filter(|bytes| bytes == b"/F1 8 T").count();
+        assert!(note_lines >= 3);
+    }
+
+    #[test]
+    fn test_ics_round_trip() {
+        let config = sample_config();
+        let work_days = sample_work_days(&config);
+        let ics = write_ics(&work_days);
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+        assert!(ics.contains("DTSTART:20241031T070000Z\r\n"));
+        assert!(ics.contains("LOCATION:Home office\r\n"));
+
+        let data = parse_ics(&ics);
+        assert_eq!(data.intervals.len(), 1);
+        assert_eq!(data.skipped, 0);
+
+        let mut calendar = std::collections::HashMap::new();
+        assert_eq!(apply_import(&mut calendar, data.clone(), ImportMode::Merge, &config), 1);
+        assert_eq!(apply_import(&mut calendar, data, ImportMode::Merge, &config), 0);
+        let work_day = calendar.get("2024-10-31").unwrap();
+        assert_eq!(work_day.sum_work.unwrap().get_hours(), 8);
+        assert_eq!(work_day.sum_work.unwrap().get_minutes(), 30);
+    }
+
+    #[test]
+    fn test_parse_ics_events() {
+        let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nDTSTART;TZID=Europe/Berlin:20241104T090000\r\n\
+                   DTEND;TZID=Europe/Berlin:20241104T101500\r\nSUMMARY:Customer call\\, with\r\n  notes\r\n\
+                   LOCATION:office\r\nEND:VEVENT\r\nBEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20241105\r\n\
+                   DTEND;VALUE=DATE:20241106\r\nSUMMARY:Holiday\r\nEND:VEVENT\r\nBEGIN:VEVENT\r\n\
+                   DTSTART:2024-11-06 09:00\r\nDTEND:20241106T100000Z\r\nSUMMARY:Broken\r\nEND:VEVENT\r\n\
+                   BEGIN:VEVENT\r\nDTSTART;TZID=W. Europe Standard Time:20241107T090000\r\n\
+                   DTEND;TZID=W. Europe Standard Time:20241107T093000\r\nSUMMARY:Standup\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let data = parse_ics(ics);
+        // the all-day and the broken event are skipped, the rest is still read
+        assert_eq!(data.skipped, 2);
+        let intervals = data.intervals;
+        assert_eq!(intervals.len(), 2);
+        let standup = intervals[1].work_times.start.as_ref().unwrap();
+        assert_eq!(standup.with_time_zone(jiff::tz::TimeZone::UTC).time(), jiff::civil::time(8, 0, 0, 0));
+        let work_times = &intervals[0].work_times;
+        assert_eq!(work_times.label.as_deref(), Some("Customer call, with notes"));
+        assert_eq!(intervals[0].location, Some(crate::gui::gui_main::Location::Office));
+        let start = work_times.start.as_ref().unwrap();
+        let end = work_times.end.as_ref().unwrap();
+        assert_eq!(span_in_hours(&start.until(end).unwrap()), 1.25);
+    }
+
+    #[test]
+    fn test_parse_csv_formats() {
+        let generic = "date,start,end,label\n2024-11-04,08:00,12:00,Project X\n2024-11-04,12:30,17:00,\n";
+        let (format, data) = parse_csv(generic).unwrap();
+        assert_eq!(format, ImportFormat::GenericCsv);
+        assert_eq!(data.intervals.len(), 2);
+        assert_eq!(data.intervals[0].work_times.label.as_deref(), Some("Project X"));
+        assert_eq!(data.intervals[1].work_times.label.as_deref(), Some("Work"));
+
+        let toggl = "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags\n\
+                     Jane,jane@example.com,ACME,Website,,Design,No,2024-11-04,09:00:00,2024-11-04,10:30:00,01:30:00,\n";
+        let (format, data) = parse_csv(toggl).unwrap();
+        assert_eq!(format, ImportFormat::Toggl);
+        assert_eq!(data.intervals[0].work_times.label.as_deref(), Some("Website"));
+
+        let clockify = "\"Project\",\"Client\",\"Description\",\"Start Date\",\"Start Time\",\"End Date\",\"End Time\",\"Duration (h)\"\n\
+                        \"\",\"ACME\",\"Review, part 2\",\"11/04/2024\",\"01:00:00 PM\",\"11/04/2024\",\"02:15:00 PM\",\"01:15:00\"\n";
+        let (format, data) = parse_csv(clockify).unwrap();
+        assert_eq!(format, ImportFormat::Clockify);
+        let work_times = &data.intervals[0].work_times;
+        assert_eq!(work_times.label.as_deref(), Some("Review, part 2"));
+        assert_eq!(work_times.start.as_ref().unwrap().time().hour(), 13);
+        assert_eq!(work_times.start.as_ref().unwrap().date(), Date::new(2024, 11, 4).unwrap());
+
+        // a quoted description spanning lines stays one record, and errors name the line the record starts on
+        let multi_line = "\"Project\",\"Description\",\"Start Date\",\"Start Time\",\"End Date\",\"End Time\",\"Duration (h)\"\r\n\
+                          \"\",\"Review\r\nand \"\"fix\"\"\",\"11/04/2024\",\"09:00:00 AM\",\"11/04/2024\",\"10:00:00 AM\",\"01:00:00\"\r\n\
+                          \"\",\"Plan\",\"11/04/2024\",\"10:00:00 AM\",\"11/04/2024\",\"11:00:00 AM\",\"01:00:00\"\r\n";
+        let (format, data) = parse_csv(multi_line).unwrap();
+        assert_eq!(format, ImportFormat::Clockify);
+        assert_eq!(data.intervals.len(), 2);
+        assert_eq!(data.intervals[0].work_times.label.as_deref(), Some("Review\r\nand \"fix\""));
+        assert_eq!(data.intervals[1].work_times.label.as_deref(), Some("Plan"));
+        let broken = "date,start,end,note\n2024-11-04,08:00,09:00,\"first\nsecond\"\n2024-11-04,9 o'clock,10:00,\n";
+        assert_eq!(parse_csv(broken).unwrap_err().split(':').next(), Some("line 4"));
+
+        let kimai = "Date;From;To;Duration;Customer;Project;Activity;Description\n04.11.2024;22:00;01:00;3:00;ACME;Release;Deploy;\n";
+        let (format, data) = parse_csv(kimai).unwrap();
+        assert_eq!(format, ImportFormat::Kimai);
+        let work_times = &data.intervals[0].work_times;
+        assert_eq!(work_times.end.as_ref().unwrap().date(), Date::new(2024, 11, 5).unwrap());
+
+        assert!(parse_csv("date,start,end\n2024-11-04,9 o'clock,10:00\n").is_err());
+    }
+
+    #[test]
+    fn test_import_own_csv_with_conflicts() {
+        let config = sample_config();
+        let own_csv = ";;;;;;\n2024-10-31;;;;;;\nIn office;SUM WORK;8:30;SUM BREAKS;;CONTINGENT;0 : 30\n;;;;;;\n\
+                       ;START;END;;DURATION;BREAK;\n;08:00;12:00;;4:0;0:30;\n;12:30;17:00;;4:30;;\n\n\
+                       2024-11-04;;;;;;\n;SUM WORK;8:0;SUM BREAKS;;CONTINGENT;0 : 30\nVACATION;;;;;;\n;START;END;;DURATION;BREAK;\n\n";
+        let (format, data) = parse_csv(own_csv).unwrap();
+        assert_eq!(format, ImportFormat::OwnCsv);
+        assert_eq!(data.intervals.len(), 2);
+        assert_eq!(data.vacation_days, vec![Date::new(2024, 11, 4).unwrap()]);
+
+        let mut calendar = std::collections::HashMap::new();
+        for work_day in sample_work_days(&config) {
+            calendar.insert(work_day.date.to_string(), work_day);
+        }
+        let preview = build_preview("export.csv".to_owned(), format, data.clone(), &calendar);
+        assert_eq!(preview.days.len(), 2);
+        assert!(preview.days[0].has_conflict());
+        assert_eq!(preview.days[0].overlapping, 2);
+        assert!(preview.days[1].vacation);
+
+        // both imported intervals overlap the existing 08:00 - 16:30, merging must not count them twice
+        let mut merged = calendar.clone();
+        assert_eq!(apply_import(&mut merged, data.clone(), ImportMode::Merge, &config), 0);
+        assert_eq!(merged["2024-10-31"].work_duration.len(), 1);
+        assert_eq!(span_in_hours(&merged["2024-10-31"].sum_work.unwrap()), 8.5);
+
+        assert_eq!(apply_import(&mut calendar, data, ImportMode::Replace, &config), 2);
+        let work_day = &calendar["2024-10-31"];
+        assert_eq!(work_day.work_duration.len(), 2);
+        assert_eq!(work_day.location, Some(crate::gui::gui_main::Location::Office));
+        assert_eq!(span_in_hours(&work_day.sum_work.unwrap()), 8.5);
+        assert_eq!(span_in_hours(&work_day.sum_pause.unwrap()), 0.5);
+        assert_eq!(span_in_hours(&calendar["2024-11-04"].sum_work.unwrap()), 8.);
+
+        // an interval still running stays the last one, also when the day is replaced
+        let running_start: jiff::Zoned = "2024-11-05T10:00[Europe/Berlin]".parse().unwrap();
+        let mut running_day = OneDaysWork::init(running_start.date());
+        running_day.work_duration.push(WorkTimes { start: Some(running_start.clone()), ..WorkTimes::init() });
+        calendar.insert(running_day.date.to_string(), running_day);
+        let earlier = ImportedInterval {
+            work_times: WorkTimes {
+                start: Some("2024-11-05T08:00[Europe/Berlin]".parse().unwrap()),
+                end: Some("2024-11-05T09:00[Europe/Berlin]".parse().unwrap()),
+                ..WorkTimes::init()
+            },
+            location: None,
+        };
+        let data = ImportedData { intervals: vec![earlier], ..Default::default() };
+        for mode in [ImportMode::Merge, ImportMode::Replace] {
+            let mut imported = calendar.clone();
+            assert_eq!(apply_import(&mut imported, data.clone(), mode, &config), 1);
+            let work_duration = &imported["2024-11-05"].work_duration;
+            assert_eq!(work_duration.len(), 2);
+            assert_eq!(work_duration.last().unwrap().start, Some(running_start.clone()));
+            assert!(work_duration.last().unwrap().end.is_none());
+        }
+    }
+
+    #[test]
+    fn test_range_report() {
+        let config = sample_config();
+        let work_days = sample_work_days(&config);
+
+        let report = build_range_report(&config, &work_days, None, None);
+        assert_eq!(report.schema_version, 1);
+        assert_eq!(report.from, Date::new(2024, 10, 31).unwrap());
+        assert_eq!(report.to, Date::new(2024, 11, 4).unwrap());
+        assert_eq!(report.totals.worked_hours, 16.5);
+        assert_eq!(report.totals.target_hours, 24.);
+        assert_eq!(report.totals.vacation_days, 1);
+
+        let json = write_json_report(&report);
+        assert!(json.contains("\"start\": \"2024-10-31T08:00:00+01:00\""));
+        assert!(json.contains("\"location\": \"home_office\""));
+        let parsed: RangeReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.days[0].intervals[0].duration_hours, Some(8.5));
+        assert_eq!(parsed.days[0].contingent_hours, 8.5 - 4. * 8.);
+
+        let from = Some(Date::new(2024, 11, 4).unwrap());
+        let to = Some(Date::new(2024, 11, 10).unwrap());
+        let report = build_range_report(&config, &work_days, from, to);
+        assert_eq!(report.days.len(), 1);
+        let markdown = write_markdown_summary(&report, &config);
+        assert!(markdown.contains("## Week 45 (2024-11-04 – 2024-11-10)"));
+        assert!(markdown.contains("| Mon 2024-11-04 | 8:00 | 0:00 | vacation |  |"));
+        assert!(markdown.contains("**Total:** 8:00 h (target 40:00 h, - 32:00)"));
+    }
+
+    fn sample_calendar() -> std::collections::HashMap<String, OneDaysWork> {
+        let mut calendar = std::collections::HashMap::new();
+        for work_day in sample_work_days(&sample_config()) {
+            calendar.insert(work_day.date.to_string(), work_day);
+        }
+        calendar
+    }
+
+    // the data file and the journal go into `dir`, which is removed when the test ends
+    fn sample_app(dir: &TempDir) -> App {
+        let data_path = dir.path().join(".work_data.json");
+        App {
+            config: sample_config(),
+            state: State::Stopped,
+            show_picker: false,
+            date: jiff::Zoned::now().date(),
+            calendar: sample_calendar(),
+            import_preview: None,
+            saved: Default::default(),
+            journaled: Default::default(),
+            reload_conflict: None,
+            journal: Journal::new(journal_path(&data_path)),
+            data_path,
+            correction: None,
+            unlock: None,
+            search: None,
+            idle_prompt: None,
+            auto_stopped: false,
+            recover: None,
+            reminders: Default::default(),
+            focus: None,
+            palette: None,
+            system_dark: true,
+            note_draft: None,
+            projects: vec!["Work".to_owned()],
+            running_on: None,
+        }
+    }
+
+    #[test]
+    fn test_api_requests() {
+        let request = parse_request("GET /report/?from=2024-10-01&to=2024-10-31 HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/report");
+        assert_eq!(request.query["from"], "2024-10-01");
+        assert!(parse_request("hello\r\n\r\n").is_none());
+
+        // only requests from this machine's tools, not from web pages or rebound domains
+        assert_eq!(forbidden("GET /status HTTP/1.1\r\nHost: localhost:7373\r\n\r\n", 7373), None);
+        assert_eq!(forbidden("GET /status HTTP/1.1\r\nhost: 127.0.0.1:7373\r\n\r\n", 7373), None);
+        assert!(forbidden("GET /status HTTP/1.1\r\nHost: evil.example:7373\r\n\r\n", 7373).is_some());
+        assert!(forbidden("GET /status HTTP/1.1\r\nHost: localhost:8080\r\n\r\n", 7373).is_some());
+        assert!(forbidden("GET /status HTTP/1.1\r\n\r\n", 7373).is_some());
+        assert!(forbidden("POST /start HTTP/1.1\r\nHost: localhost:7373\r\nOrigin: http://localhost:7373\r\n\r\n", 7373).is_some());
+
+        let dir = tempdir().unwrap();
+        let mut app = sample_app(&dir);
+        let report = handle_request(&mut app, &request);
+        assert_eq!(report.status, 200);
+        assert_eq!(report.body["days"].as_array().unwrap().len(), 1);
+        assert_eq!(report.body["totals"]["worked_hours"], 8.5);
+
+        let bad_range = parse_request("GET /report?from=yesterday HTTP/1.1\r\n\r\n").unwrap();
+        assert_eq!(handle_request(&mut app, &bad_range).status, 400);
+
+        let start = parse_request("POST /start HTTP/1.1\r\n\r\n").unwrap();
+        let stop = parse_request("POST /stop HTTP/1.1\r\n\r\n").unwrap();
+        let status = parse_request("GET /status HTTP/1.1\r\n\r\n").unwrap();
+        assert_eq!(handle_request(&mut app, &status).body["running"], false);
+        assert_eq!(handle_request(&mut app, &stop).status, 409);
+
+        let started = handle_request(&mut app, &start);
+        assert_eq!(started.status, 200);
+        assert_eq!(started.body["running"], true);
+        assert!(matches!(app.state, State::Started));
+        assert_eq!(handle_request(&mut app, &start).status, 409);
+
+        let today = handle_request(&mut app, &parse_request("GET /today HTTP/1.1\r\n\r\n").unwrap());
+        assert_eq!(today.body["days"][0]["intervals"][0]["end"], serde_json::Value::Null);
+
+        assert_eq!(handle_request(&mut app, &stop).body["running"], false);
+        assert!(matches!(app.state, State::Stopped));
+        assert_eq!(handle_request(&mut app, &parse_request("GET /start HTTP/1.1\r\n\r\n").unwrap()).status, 405);
+        assert_eq!(handle_request(&mut app, &parse_request("GET /nope HTTP/1.1\r\n\r\n").unwrap()).status, 404);
+    }
+
+    #[test]
+    fn test_merge_external_changes() {
+        let config = sample_config();
+        let base = sample_calendar();
+        let (worked, vacation) = ("2024-10-31".to_owned(), "2024-11-04".to_owned());
+
+        // the file got a location for one day and a new day, the app changed another day
+        let mut remote = base.clone();
+        remote.get_mut(&worked).unwrap().location = Some(crate::gui::gui_main::Location::Office);
+        let new_day = Date::new(2024, 11, 5).unwrap();
+        remote.insert(new_day.to_string(), OneDaysWork::init(new_day));
+        let mut local = base.clone();
+        local.get_mut(&vacation).unwrap().added_workday = true;
+
+        let merged = merge_calendars(&base, &local, &remote);
+        assert!(merged.conflicts.is_empty());
+        assert_eq!(merged.calendar.len(), 3);
+        assert!(same_day(merged.calendar.get(&worked), remote.get(&worked)));
+        assert!(merged.calendar[&vacation].added_workday);
+
+        // a day removed in the file disappears unless it was edited here too
+        let mut remote = base.clone();
+        remote.remove(&vacation);
+        assert!(!merge_calendars(&base, &base, &remote).calendar.contains_key(&vacation));
+
+        // both sides edited the same day differently: the local version wins and is reported
+        let mut remote = base.clone();
+        remote.get_mut(&worked).unwrap().work_duration.clear();
+        remote.get_mut(&worked).unwrap().recompute(config.get_workday_span());
+        let mut local = base.clone();
+        local.get_mut(&worked).unwrap().location = Some(crate::gui::gui_main::Location::Office);
+        let merged = merge_calendars(&base, &local, &remote);
+        assert_eq!(merged.conflicts, vec![Date::new(2024, 10, 31).unwrap()]);
+        assert!(same_day(merged.calendar.get(&worked), local.get(&worked)));
+
+        // while the conflict is open the file isn't overwritten, not even by other edits
+        use crate::gui::serialize::{try_read_calendar, Calendar};
+        let dir = tempdir().unwrap();
+        let mut app = sample_app(&dir);
+        let file_day = || try_read_calendar(&dir.path().join(".work_data.json")).unwrap().into_hashmap().remove(&worked);
+        Calendar::update(&remote, &app.data_path);
+        app.calendar = local.clone();
+        app.reload();
+        assert!(app.reload_conflict.is_some());
+        let _ = app.update(Message::ToggleVacation);
+        let _ = app.update(Message::ToggleGenericWorkday);
+        assert!(same_day(file_day().as_ref(), remote.get(&worked)));
+
+        // each of those actions is journaled once, not again with every later action
+        let entries = read_journal(&app.journal.path).unwrap();
+        let actions: Vec<usize> = entries.iter().rev().take(2).map(|entry| entry.changes.len()).collect();
+        assert_eq!(actions, vec![1, 1]);
+        let _ = app.update(Message::KeepLocalChanges);
+        assert!(app.reload_conflict.is_none());
+        assert!(same_day(file_day().as_ref(), local.get(&worked)));
+    }
+
+    #[test]
+    fn test_sync_logs_of_two_devices() {
+        let dir = tempdir().unwrap();
+        let sync_dir = dir.path().to_path_buf();
+        let laptop = Config {
+            sync_dir: Some(sync_dir.clone()),
+            device_name: "laptop".to_owned(),
+            ..sample_config()
+        };
+        let desktop = Config {
+            device_name: "desktop".to_owned(),
+            ..laptop.clone()
+        };
+
+        let base = sample_calendar();
+        append_changes(&laptop, diff_calendars(&Default::default(), &base)).unwrap();
+        let synced = merge_logs(read_logs(&sync_dir), laptop.get_workday_span());
+        assert_eq!(synced.len(), 2);
+        assert_eq!(span_in_hours(&synced["2024-10-31"].sum_work.unwrap()), 8.5);
+        assert!(synced["2024-11-04"].vacation);
+
+        // an encrypted device never writes its work times into the plaintext logs
+        let encrypted = Config { device_name: "phone".to_owned(), encrypt_data: true, ..laptop.clone() };
+        append_changes(&encrypted, diff_calendars(&Default::default(), &base)).unwrap();
+        assert!(!crate::gui::sync::log_path(&sync_dir, "phone").exists());
+
+        // both devices add an interval to the same day without seeing the other one
+        let interval = |start: &str, end: &str| WorkTimes {
+            label: Some("Work".to_owned()),
+            start: Some(start.parse().unwrap()),
+            end: Some(end.parse().unwrap()),
+            ..WorkTimes::init()
+        };
+        let mut on_laptop = synced.clone();
+        on_laptop.get_mut("2024-10-31").unwrap().work_duration.push(interval("2024-10-31T17:00[Europe/Berlin]", "2024-10-31T18:00[Europe/Berlin]"));
+        append_changes(&laptop, diff_calendars(&synced, &on_laptop)).unwrap();
+        let mut on_desktop = synced.clone();
+        on_desktop.get_mut("2024-10-31").unwrap().work_duration.push(interval("2024-10-31T19:00[Europe/Berlin]", "2024-10-31T19:30[Europe/Berlin]"));
+        on_desktop.get_mut("2024-10-31").unwrap().location = Some(crate::gui::gui_main::Location::Office);
+        append_changes(&desktop, diff_calendars(&synced, &on_desktop)).unwrap();
+
+        let mut entries = read_logs(&sync_dir);
+        let merged = merge_logs(entries.clone(), laptop.get_workday_span());
+        let work_day = &merged["2024-10-31"];
+        assert_eq!(work_day.work_duration.len(), 3);
+        assert_eq!(work_day.location, Some(crate::gui::gui_main::Location::Office));
+        assert_eq!(span_in_hours(&work_day.sum_work.unwrap()), 10.);
+
+        // the order the logs are read in doesn't matter
+        entries.reverse();
+        let reversed = merge_logs(entries, laptop.get_workday_span());
+        assert!(merged.iter().all(|(key, work_day)| same_day(Some(work_day), reversed.get(key))));
+
+        // removing an interval on one device removes it everywhere
+        let mut removed = merged.clone();
+        removed.get_mut("2024-10-31").unwrap().work_duration.remove(0);
+        append_changes(&desktop, diff_calendars(&merged, &removed)).unwrap();
+        let merged = merge_logs(read_logs(&sync_dir), laptop.get_workday_span());
+        assert_eq!(merged["2024-10-31"].work_duration.len(), 2);
+    }
+
+    #[test]
+    fn test_journal_undo_redo() {
+        let dir = tempdir().unwrap();
+        let mut app = sample_app(&dir);
+        let path = app.journal.path.clone();
+        app.date = jiff::civil::date(2024, 10, 31);
+        let span = app.config.get_workday_span();
+
+        app.saved = app.calendar.clone();
+        app.journal.record("snapshot", &Default::default(), &app.saved);
+        app.calendar.get_mut("2024-10-31").unwrap().location = Some(crate::gui::gui_main::Location::Office);
+        let before = app.saved.clone();
+        app.journal.record("location", &before, &app.calendar);
+        app.saved = app.calendar.clone();
+        app.calendar.get_mut("2024-11-04").unwrap().vacation = false;
+        app.calendar.get_mut("2024-11-04").unwrap().recompute(span);
+        let before = app.saved.clone();
+        app.journal.record("vacation", &before, &app.calendar);
+
+        assert!(app.journal.undo(&mut app.calendar, span).is_some());
+        assert!(app.calendar["2024-11-04"].vacation);
+        assert_eq!(span_in_hours(&app.calendar["2024-11-04"].sum_work.unwrap()), 8.);
+        assert!(app.journal.undo(&mut app.calendar, span).is_some());
+        assert_eq!(app.calendar["2024-10-31"].location, Some(crate::gui::gui_main::Location::Homeoffice));
+        assert!(app.journal.undo(&mut app.calendar, span).is_some());
+        assert!(app.calendar.is_empty());
+        assert!(app.journal.undo(&mut app.calendar, span).is_none());
+
+        assert!(app.journal.redo(&mut app.calendar, span).is_some());
+        assert!(app.journal.redo(&mut app.calendar, span).is_some());
+        assert_eq!(app.calendar["2024-10-31"].location, Some(crate::gui::gui_main::Location::Office));
+        assert!(app.calendar["2024-11-04"].vacation);
+
+        // undo and redo are journaled too, replaying everything gives the current state
+        let entries = read_journal(&path).unwrap();
+        assert_eq!(entries.len(), 8);
+        assert_eq!(entries[3].action, "undo");
+        let rebuilt = rebuild(entries, span);
+        assert!(app.calendar.iter().all(|(key, work_day)| same_day(Some(work_day), rebuilt.get(key))));
+        assert_eq!(rebuilt.len(), app.calendar.len());
+
+        // a change from another device is journaled but neither it nor anything before it can be undone
+        let before = app.calendar.clone();
+        app.calendar.get_mut("2024-10-31").unwrap().note = "from the laptop".to_owned();
+        assert_eq!(app.journal.record_foreign("sync", &before, &app.calendar).len(), 1);
+        assert!(app.journal.undo(&mut app.calendar, span).is_none());
+        assert_eq!(app.calendar["2024-10-31"].note, "from the laptop");
+        assert_eq!(read_journal(&path).unwrap().len(), 9);
+    }
+
+    #[test]
+    fn test_correct_interval() {
+        let config = sample_config();
+        let span = config.get_workday_span();
+        let mut work_days = sample_work_days(&config);
+        let original_start = work_days[0].work_duration[0].start.clone().unwrap();
+        let start = parse_corrected_time("07:45", &original_start).unwrap();
+        let end = parse_corrected_time("16:30", &original_start).unwrap();
+        assert_eq!(start.time_zone().iana_name(), Some("Europe/Berlin"));
+        assert!(parse_corrected_time("7.45", &original_start).is_err());
+
+        assert!(work_days[0].correct_interval(0, start.clone(), Some(end.clone()), " ", span).is_err());
+        assert!(work_days[0].correct_interval(0, end.clone(), Some(start.clone()), "typo", span).is_err());
+        work_days[0].correct_interval(0, start.clone(), Some(end), "forgot to clock in", span).unwrap();
+        let work_times = &work_days[0].work_duration[0];
+        assert!(work_times.is_corrected());
+        assert_eq!(work_times.corrections[0].original_start, Some(original_start));
+        assert_eq!(span_in_hours(&work_days[0].sum_work.unwrap()), 8.75);
+
+        let corrections = collect_corrections(&work_days, Some(Date::new(2024, 10, 1).unwrap()), Some(Date::new(2024, 10, 31).unwrap()));
+        assert_eq!(corrections.len(), 1);
+        assert_eq!(corrections[0].original_start.as_deref(), Some("2024-10-31T08:00:00+01:00"));
+        assert_eq!(corrections[0].start.as_deref(), Some("2024-10-31T07:45:00+01:00"));
+        assert!(write_corrections_csv(&corrections).ends_with(";\"forgot to clock in\"\n"));
+        assert!(collect_corrections(&work_days, Some(Date::new(2024, 11, 1).unwrap()), None).is_empty());
+
+        // marked in the exports
+        let report = build_range_report(&config, &work_days, None, None);
+        assert_eq!(report.days[0].intervals[0].corrections.len(), 1);
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("corrected_report.html");
+        export_report(&path, &config, work_days.clone(), Date::new(2024, 10, 1).unwrap(), ReportFormat::Html).unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap().contains("<td>07:45 *</td>"));
+
+        // the correction travels through the journal and sync changes
+        let mut before = std::collections::HashMap::new();
+        before.insert("2024-10-31".to_owned(), sample_work_days(&config).remove(0));
+        let mut after = before.clone();
+        after.insert("2024-10-31".to_owned(), work_days[0].clone());
+        let mut replayed = before.clone();
+        crate::gui::sync::apply_changes(&mut replayed, diff_calendars(&before, &after), span);
+        assert_eq!(replayed["2024-10-31"].work_duration.len(), 1);
+        assert!(replayed["2024-10-31"].work_duration[0].is_corrected());
+
+        // an edited reason is a change even though the number of corrections stays the same
+        let mut edited = after.clone();
+        edited.get_mut("2024-10-31").unwrap().work_duration[0].corrections[0].reason = "came early".to_owned();
+        assert_eq!(diff_calendars(&after, &edited).len(), 1);
+
+        // only the last interval may be left running
+        let evening = parse_corrected_time("18:00", &start).unwrap();
+        let mut two_intervals = work_days[0].clone();
+        two_intervals.work_duration.push(WorkTimes { start: Some(evening.clone()), ..WorkTimes::init() });
+        assert!(two_intervals.correct_interval(0, start.clone(), None, "still working", span).is_err());
+        assert!(two_intervals.correct_interval(1, evening, None, "still working", span).is_ok());
+    }
+
+    #[test]
+    fn test_encrypted_data() {
+        assert!(DataKey::new("short").is_err());
+        let data_key = DataKey::new("correct horse battery").unwrap();
+        let plaintext = r#"{"work_days":[{"date":"2024-10-31","location":"Homeoffice"}]}"#;
+
+        let content = data_key.encrypt(plaintext);
+        assert!(is_encrypted(&content));
+        assert!(!is_encrypted(plaintext));
+        assert!(!content.contains("2024-10-31") && !content.contains("Homeoffice"));
+        assert_ne!(content, data_key.encrypt(plaintext));
+        assert_eq!(data_key.decrypt(&content).unwrap(), plaintext);
+
+        // the passphrase is checked against the file, a wrong one or a modified file fail
+        let unlocked = DataKey::for_file_content("correct horse battery", &content).unwrap();
+        assert_eq!(unlocked.decrypt(&content).unwrap(), plaintext);
+        assert!(DataKey::for_file_content("wrong horse battery", &content).is_err());
+        let position = content.find("\"ciphertext\":\"").unwrap() + 14;
+        let flipped = match &content[position..position + 1] {
+            "0" => "1",
+            _ => "0",
+        };
+        let tampered = format!("{}{flipped}{}", &content[..position], &content[position + 1..]);
+        assert!(data_key.decrypt(&tampered).is_err());
+
+        let line = data_key.encrypt_line(r#"{"action":"start"}"#);
+        assert!(line.starts_with("enc:"));
+        assert_eq!(unlocked.decrypt_line(&line).unwrap(), r#"{"action":"start"}"#);
+
+        // the command line never encrypts a plaintext file with an unconfirmed passphrase
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(".work_data.json");
+        std::fs::write(&path, plaintext).unwrap();
+        let config = Config { encrypt_data: true, ..sample_config() };
+        assert!(crate::gui::crypto::unlock_from_terminal(&config, &path).is_err());
+        assert!(!is_encrypted(&std::fs::read_to_string(&path).unwrap()));
+    }
+
+    #[test]
+    fn test_notes() {
+        let config = sample_config();
+        let mut work_days = sample_work_days(&config);
+        work_days[0].note = "Release day".to_owned();
+        work_days[0].work_duration[0].note = "customer call; deployment\nreview".to_owned();
+        assert!(work_days[0].matches_text("release"));
+        assert!(work_days[0].matches_text("CUSTOMER call"));
+        assert!(!work_days[0].matches_text("holiday"));
+        assert!(!work_days[1].matches_text("release"));
+
+        // the own csv keeps the notes in the last column
+        let csv = serialize_calendar_to_csv(work_days.clone(), &config);
+        assert!(csv.contains("2024-10-31;;;;;;Release day\n"));
+        assert!(csv.contains(";08:00;16:30;;8:30;;customer call, deployment review\n"));
+        let (_, data) = parse_csv(&csv).unwrap();
+        assert_eq!(data.day_notes, vec![(Date::new(2024, 10, 31).unwrap(), "Release day".to_owned())]);
+        assert_eq!(data.intervals[0].work_times.note, "customer call, deployment review");
+
+        let intervals = parse_ics(&write_ics(&work_days)).intervals;
+        assert_eq!(intervals[0].work_times.note, "customer call; deployment\nreview");
+
+        let report = build_range_report(&config, &work_days, None, None);
+        assert_eq!(report.days[0].note, "Release day");
+        let markdown = write_markdown_summary(&report, &config);
+        assert!(markdown.contains("| Release day; customer call; deployment review |"));
+
+        // notes travel through the journal and sync changes
+        let mut before = std::collections::HashMap::new();
+        before.insert("2024-10-31".to_owned(), sample_work_days(&config).remove(0));
+        let mut after = before.clone();
+        after.insert("2024-10-31".to_owned(), work_days[0].clone());
+        let mut replayed = before.clone();
+        crate::gui::sync::apply_changes(&mut replayed, diff_calendars(&before, &after), config.get_workday_span());
+        assert_eq!(replayed["2024-10-31"].note, "Release day");
+        assert_eq!(replayed["2024-10-31"].work_duration[0].note, work_days[0].work_duration[0].note);
+
+        let dir = tempdir().unwrap();
+        let mut app = sample_app(&dir);
+        app.calendar.insert("2024-10-31".to_owned(), work_days[0].clone());
+        let found = handle_request(&mut app, &parse_request("GET /search?q=customer+call HTTP/1.1\r\n\r\n").unwrap());
+        assert_eq!(found.body.as_array().unwrap().len(), 1);
+        assert_eq!(found.body[0]["intervals"][0]["note"], "customer call; deployment\nreview");
+        let empty = parse_request("GET /search?q= HTTP/1.1\r\n\r\n").unwrap();
+        assert_eq!(handle_request(&mut app, &empty).status, 400);
+
+        // typing only changes the draft, opening another day journals the note once
+        app.date = Date::new(2024, 10, 31).unwrap();
+        let journal_path = app.journal.path.clone();
+        let journaled = || read_journal(&journal_path).map(|entries| entries.len()).unwrap_or(0);
+        let before = journaled();
+        for note in ["R", "Re", "Review"] {
+            let _ = app.update(Message::DayNoteChanged(note.to_owned()));
+        }
+        assert_eq!(app.calendar["2024-10-31"].note, "Release day");
+        assert_eq!(journaled(), before);
+        let _ = app.update(Message::NextDay);
+        assert_eq!(app.calendar["2024-10-31"].note, "Review");
+        assert!(app.note_draft.is_none());
+        assert_eq!(journaled(), before + 1);
+    }
+
+    #[test]
+    fn test_search() {
+        let config = sample_config();
+        let mut work_days = sample_work_days(&config);
+        let start: jiff::Zoned = "2024-11-05T09:00[Europe/Berlin]".parse().unwrap();
+        let end: jiff::Zoned = "2024-11-05T11:30[Europe/Berlin]".parse().unwrap();
+        let mut work_day = OneDaysWork::init(start.date());
+        work_day.location = Some(crate::gui::gui_main::Location::Office);
+        work_day.work_duration.push(WorkTimes {
+            label: Some("Website".to_owned()),
+            duration: Some(start.until(&end).unwrap()),
+            start: Some(start),
+            end: Some(end),
+            note: "fixed the #Support ticket".to_owned(),
+            ..WorkTimes::init()
+        });
+        work_day.sum_durations();
+        work_days.push(work_day);
+        work_days[1].note = "trip to the sea".to_owned();
+
+        let everything = search(&work_days, &SearchQuery::default());
+        assert_eq!(everything.matches.len(), 3);
+        assert_eq!(everything.days, 3);
+        assert_eq!(everything.total_hours, 19.);
+
+        let project = SearchQuery {
+            project: "website".to_owned(),
+            ..Default::default()
+        };
+        let result = search(&work_days, &project);
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.total_hours, 2.5);
+        assert_eq!(result.matches[0].start.as_deref(), Some("09:00"));
+
+        let tag = SearchQuery {
+            tag: "#support".to_owned(),
+            from: Some(Date::new(2024, 11, 1).unwrap()),
+            to: Some(Date::new(2024, 11, 30).unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(search(&work_days, &tag).days, 1);
+        let in_october = SearchQuery {
+            to: Some(Date::new(2024, 10, 31).unwrap()),
+            ..tag
+        };
+        assert!(search(&work_days, &in_october).matches.is_empty());
+
+        let vacation = SearchQuery {
+            text: "SEA".to_owned(),
+            absence: Some(Absence::Vacation),
+            ..Default::default()
+        };
+        let result = search(&work_days, &vacation);
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.total_hours, 8.);
+        let office = SearchQuery {
+            location: Some(crate::gui::gui_main::Location::Office),
+            ..Default::default()
+        };
+        assert_eq!(search(&work_days, &office).matches[0].date, Date::new(2024, 11, 5).unwrap());
+
+        let mut form = SearchForm {
+            from: "March".to_owned(),
+            ..Default::default()
+        };
+        assert!(form.parse_dates().is_err());
+        form.from = "2024-03-01".to_owned();
+        form.parse_dates().unwrap();
+        assert_eq!(form.query.from, Some(Date::new(2024, 3, 1).unwrap()));
+    }
+
+    // replays idle times in seconds, one per poll
+    struct FakeIdle(Vec<u64>);
+
+    impl IdleSource for FakeIdle {
+        fn idle_time(&mut self) -> Option<std::time::Duration> {
+            (!self.0.is_empty()).then(|| std::time::Duration::from_secs(self.0.remove(0)))
+        }
+    }
+
+    #[test]
+    fn test_idle_detection() {
+        let mut source = FakeIdle(vec![5, 300, 900, 1500, 3]);
+        let mut detector = IdleDetector::new(std::time::Duration::from_secs(600));
+        let at = |time: &str| -> jiff::Timestamp { format!("2024-10-31T{time}+01:00").parse().unwrap() };
+        assert_eq!(detector.poll(&mut source, at("10:05:00")), None);
+        assert_eq!(detector.poll(&mut source, at("10:10:00")), None);
+        assert_eq!(detector.poll(&mut source, at("10:20:00")), None);
+        assert_eq!(detector.poll(&mut source, at("10:30:00")), None);
+        let period = detector.poll(&mut source, at("10:45:03")).unwrap();
+        assert_eq!(period, IdlePeriod { from: at("10:05:00"), to: at("10:45:00") });
+        assert_eq!(period.duration().as_secs(), 2400);
+        assert_eq!(detector.poll(&mut source, at("10:50:00")), None);
+
+        assert_eq!(parse_idle_hint("IdleHint=yes\nIdleSinceHint=1730365500000000\n"), Some((true, 1730365500000000)));
+        assert_eq!(parse_idle_hint("IdleHint=no\n"), None);
+
+        let config = sample_config();
+        let span = config.get_workday_span();
+        let zoned = |time: &str| -> jiff::Zoned { format!("2024-10-31T{time}[Europe/Berlin]").parse().unwrap() };
+        let mut work_day = OneDaysWork::init(Date::new(2024, 10, 31).unwrap());
+        work_day.work_duration.push(WorkTimes {
+            label: Some("Website".to_owned()),
+            start: Some(zoned("08:00")),
+            ..WorkTimes::init()
+        });
+
+        // as a break the interval is split and tracking goes on
+        let mut as_break = work_day.clone();
+        assert!(as_break.split_running(zoned("10:05"), Some(zoned("10:45")), span));
+        assert_eq!(as_break.work_duration.len(), 2);
+        assert_eq!(span_in_hours(&as_break.sum_work.unwrap()), 125. / 60.);
+        assert_eq!(span_in_hours(&as_break.sum_pause.unwrap()), 40. / 60.);
+        assert_eq!(as_break.work_duration[1].label.as_deref(), Some("Website"));
+        assert!(as_break.work_duration[1].end.is_none());
+
+        // discarded the interval ends where the user left
+        let mut discarded = work_day.clone();
+        assert!(discarded.split_running(zoned("10:05"), None, span));
+        assert_eq!(discarded.work_duration.len(), 1);
+        assert_eq!(discarded.work_duration[0].end, Some(zoned("10:05")));
+        assert!(!discarded.split_running(zoned("10:05"), None, span));
+
+        // idle since before the start, the interval had no work in it
+        let mut nothing = work_day.clone();
+        assert!(nothing.split_running(zoned("07:50"), Some(zoned("09:00")), span));
+        assert_eq!(nothing.work_duration.len(), 1);
+        assert_eq!(nothing.work_duration[0].start, Some(zoned("09:00")));
+
+        // away over midnight, the break ends on the next day and tracking continues there
+        let time_zone = jiff::tz::TimeZone::system();
+        let late = |day: i8, hour: i8, minute: i8| jiff::civil::date(2024, 10, day).at(hour, minute, 0, 0).to_zoned(time_zone.clone()).unwrap();
+        let dir = tempdir().unwrap();
+        let mut app = sample_app(&dir);
+        let mut evening = OneDaysWork::init(Date::new(2024, 10, 30).unwrap());
+        evening.work_duration.push(WorkTimes { label: Some("Website".to_owned()), start: Some(late(30, 22, 0)), ..WorkTimes::init() });
+        app.calendar.insert("2024-10-30".to_owned(), evening);
+        app.idle_prompt = Some(IdlePeriod { from: late(30, 23, 30).timestamp(), to: late(31, 0, 30).timestamp() });
+        app.resolve_idle(true);
+        assert_eq!(app.calendar["2024-10-30"].work_duration[0].end, Some(late(30, 23, 30)));
+        let resumed = app.calendar["2024-10-31"].work_duration.last().unwrap();
+        assert_eq!(resumed.start, Some(late(31, 0, 30)));
+        assert_eq!(resumed.label.as_deref(), Some("Website"));
+        assert!(resumed.end.is_none());
+
+        // still running at midnight, the interval ends with yesterday and goes on today
+        let today = jiff::Zoned::now().date();
+        let yesterday = today.yesterday().unwrap();
+        let at = |date: Date, hour: i8, minute: i8| date.at(hour, minute, 0, 0).to_zoned(time_zone.clone()).unwrap();
+        let mut app = sample_app(&dir);
+        let mut evening = OneDaysWork::init(yesterday);
+        evening.work_duration.push(WorkTimes { label: Some("Website".to_owned()), start: Some(at(yesterday, 22, 0)), ..WorkTimes::init() });
+        app.calendar.insert(yesterday.to_string(), evening);
+        let mut restarted = app.clone();
+        app.running_on = Some(yesterday);
+        let _ = app.update(Message::Tick);
+        assert_eq!(app.calendar[&yesterday.to_string()].work_duration[0].end, Some(at(today, 0, 0)));
+        assert_eq!(span_in_hours(&app.calendar[&yesterday.to_string()].sum_work.unwrap()), 2.);
+        assert!(app.is_running());
+        assert_eq!(app.calendar[&today.to_string()].running().unwrap().start, Some(at(today, 0, 0)));
+        assert!(matches!(app.state, State::Started));
+
+        // away from before midnight, the continued part is dropped as well
+        app.idle_prompt = Some(IdlePeriod { from: at(yesterday, 23, 30).timestamp(), to: at(today, 0, 30).timestamp() });
+        app.resolve_idle(true);
+        assert_eq!(app.calendar[&yesterday.to_string()].work_duration[0].end, Some(at(yesterday, 23, 30)));
+        let work_day = &app.calendar[&today.to_string()];
+        assert_eq!(work_day.work_duration.len(), 1);
+        assert_eq!(work_day.running().unwrap().start, Some(at(today, 0, 30)));
+        assert_eq!(work_day.running().unwrap().label.as_deref(), Some("Website"));
+
+        // left open by an earlier run, the recover form decides how it ended and nothing runs today
+        restarted.recover = RecoverForm::new(find_dangling(&restarted.calendar, today, &[], restarted.config.get_workday_span()));
+        assert!(restarted.recover.is_some());
+        let _ = restarted.update(Message::Tick);
+        assert!(restarted.calendar[&yesterday.to_string()].running().is_some());
+        assert!(restarted.calendar.get(&today.to_string()).is_none_or(|work_day| work_day.work_duration.is_empty()));
+        assert!(!restarted.is_running());
+        restarted.recover = None;
+        let _ = restarted.update(Message::Tick);
+        assert!(restarted.calendar.get(&today.to_string()).is_none_or(|work_day| work_day.work_duration.is_empty()));
+    }
+
+    #[test]
+    fn test_session_events() {
+        let mut config = Config {
+            stop_on_suspend: true,
+            ..sample_config()
+        };
+        assert_eq!(session_action(&config, SessionEvent::Suspend, true, false), SessionAction::Stop);
+        assert_eq!(session_action(&config, SessionEvent::Shutdown, true, false), SessionAction::Stop);
+        assert_eq!(session_action(&config, SessionEvent::Suspend, false, false), SessionAction::Nothing);
+        assert_eq!(session_action(&config, SessionEvent::Lock, true, false), SessionAction::Nothing);
+        assert_eq!(session_action(&config, SessionEvent::Resume, false, true), SessionAction::Nothing);
+
+        config.stop_on_lock = true;
+        config.start_on_resume = true;
+        assert_eq!(session_action(&config, SessionEvent::Lock, true, false), SessionAction::Stop);
+        assert_eq!(session_action(&config, SessionEvent::Unlock, false, true), SessionAction::Start);
+        assert_eq!(session_action(&config, SessionEvent::Resume, false, true), SessionAction::Start);
+        // stopped by hand, resume doesn't start it again
+        assert_eq!(session_action(&config, SessionEvent::Resume, false, false), SessionAction::Nothing);
+
+        let dir = tempdir().unwrap();
+        let mut app = sample_app(&dir);
+        app.config = config;
+        app.start_work();
+        app.handle_session_event(SessionEvent::Suspend);
+        assert!(!app.is_running());
+        assert!(app.auto_stopped);
+        app.handle_session_event(SessionEvent::Resume);
+        assert!(app.is_running());
+        assert!(!app.auto_stopped);
+        app.stop_work();
+    }
+
+    #[test]
+    fn test_recover_dangling_intervals() {
+        let config = sample_config();
+        let span = config.get_workday_span();
+        let zoned = |date_time: &str| -> jiff::Zoned { format!("{date_time}[Europe/Berlin]").parse().unwrap() };
+        let mut calendar = std::collections::HashMap::new();
+        for (date, start) in [("2024-10-29", "2024-10-29T09:00"), ("2024-10-30", "2024-10-30T20:00"), ("2024-11-05", "2024-11-05T08:00")] {
+            let mut work_day = OneDaysWork::init(date.parse().unwrap());
+            work_day.work_duration.push(WorkTimes {
+                start: Some(zoned(start)),
+                ..WorkTimes::init()
+            });
+            calendar.insert(date.to_owned(), work_day);
+        }
+        let activities = [zoned("2024-10-29T09:00:02").timestamp(), zoned("2024-10-29T16:42").timestamp(), zoned("2024-10-31T08:00").timestamp()];
+
+        // today's running interval is not dangling
+        let dangling = find_dangling(&calendar, Date::new(2024, 11, 5).unwrap(), &activities, span);
+        assert_eq!(dangling.len(), 2);
+        assert_eq!(dangling[0].suggested_end, zoned("2024-10-29T16:42"));
+        // no activity that day, a workday but not past midnight
+        assert_eq!(dangling[1].suggested_end, zoned("2024-10-30T23:59"));
+
+        let dir = tempdir().unwrap();
+        let mut app = sample_app(&dir);
+        app.calendar = calendar;
+        app.recover = RecoverForm::new(dangling);
+        assert_eq!(app.recover.as_ref().unwrap().end, "16:42");
+        app.recover.as_mut().unwrap().end = "25:00".to_owned();
+        app.close_dangling();
+        assert!(app.recover.as_ref().unwrap().error.is_some());
+        app.recover.as_mut().unwrap().end = "17:00".to_owned();
+        app.close_dangling();
+        let work_day = &app.calendar["2024-10-29"];
+        assert_eq!(work_day.work_duration[0].end, Some(zoned("2024-10-29T17:00")));
+        assert!(work_day.work_duration[0].is_corrected());
+        assert_eq!(span_in_hours(&work_day.sum_work.unwrap()), 8.);
+        assert_eq!(app.recover.as_ref().unwrap().intervals[0].date, Date::new(2024, 10, 30).unwrap());
+        // an end before the start is after midnight
+        app.recover.as_mut().unwrap().end = "01:00".to_owned();
+        app.close_dangling();
+        assert!(app.recover.is_none());
+        let work_day = &app.calendar["2024-10-30"];
+        assert_eq!(work_day.work_duration[0].end, Some(zoned("2024-10-31T01:00")));
+        assert_eq!(span_in_hours(&work_day.sum_work.unwrap()), 5.);
+    }
+
+    #[test]
+    fn test_due_reminders() {
+        let config = Config {
+            clock_in_reminder: Some(jiff::civil::time(10, 0, 0, 0)),
+            running_reminder: Some(jiff::civil::time(20, 0, 0, 0)),
+            max_daily_hours: Some(10.),
+            break_after_hours: Some(6.),
+            ..sample_config()
+        };
+        let zoned = |date_time: &str| -> jiff::Zoned { format!("{date_time}[Europe/Berlin]").parse().unwrap() };
+        let running_since = |start: &str| {
+            let mut work_day = OneDaysWork::init(zoned(start).date());
+            work_day.work_duration.push(WorkTimes {
+                start: Some(zoned(start)),
+                ..WorkTimes::init()
+            });
+            work_day
+        };
+
+        // a thursday, nothing recorded
+        assert!(due_reminders(&config, None, &zoned("2024-10-31T09:59")).is_empty());
+        assert_eq!(due_reminders(&config, None, &zoned("2024-10-31T10:05")), vec![Reminder::ClockIn]);
+        assert!(due_reminders(&config, None, &zoned("2024-11-02T10:05")).is_empty());
+        let mut vacation_day = OneDaysWork::init(Date::new(2024, 10, 31).unwrap());
+        vacation_day.vacation = true;
+        assert!(due_reminders(&config, Some(&vacation_day), &zoned("2024-10-31T10:05")).is_empty());
+
+        let work_day = running_since("2024-10-31T08:00");
+        assert!(due_reminders(&config, Some(&work_day), &zoned("2024-10-31T13:59")).is_empty());
+        assert_eq!(due_reminders(&config, Some(&work_day), &zoned("2024-10-31T14:00")), vec![Reminder::BreakDue]);
+        assert_eq!(
+            due_reminders(&config, Some(&work_day), &zoned("2024-10-31T16:00")),
+            vec![Reminder::TargetReached, Reminder::BreakDue]
+        );
+
+        // a 30 minute break was taken
+        let mut work_day = running_since("2024-10-31T08:00");
+        work_day.work_duration[0].end = Some(zoned("2024-10-31T12:00"));
+        work_day.work_duration.push(WorkTimes {
+            start: Some(zoned("2024-10-31T12:30")),
+            ..WorkTimes::init()
+        });
+        work_day.recompute(config.get_workday_span());
+        assert_eq!(
+            due_reminders(&config, Some(&work_day), &zoned("2024-10-31T20:05")),
+            vec![Reminder::TargetReached, Reminder::MaximumReached, Reminder::StillRunning]
+        );
+        // nothing is running any more
+        work_day.work_duration[1].end = Some(zoned("2024-10-31T20:10"));
+        work_day.recompute(config.get_workday_span());
+        assert!(due_reminders(&config, Some(&work_day), &zoned("2024-10-31T20:15")).is_empty());
+
+        let off = Config {
+            clock_in_reminder: None,
+            max_daily_hours: None,
+            break_after_hours: None,
+            ..config.clone()
+        };
+        assert!(due_reminders(&off, None, &zoned("2024-10-31T10:05")).is_empty());
+
+        let mut sent = SentReminders::default();
+        let thursday = Date::new(2024, 10, 31).unwrap();
+        assert_eq!(sent.take_new(thursday, vec![Reminder::BreakDue]), vec![Reminder::BreakDue]);
+        assert_eq!(sent.take_new(thursday, vec![Reminder::BreakDue, Reminder::TargetReached]), vec![Reminder::TargetReached]);
+        assert_eq!(sent.take_new(thursday.tomorrow().unwrap(), vec![Reminder::BreakDue]), vec![Reminder::BreakDue]);
+    }
+
+    #[test]
+    fn test_tray_menu() {
+        let dir = tempdir().unwrap();
+        let mut app = sample_app(&dir);
+        let status = app.tray_status();
+        assert!(!status.running);
+        assert_eq!(status.projects, vec!["Work".to_owned()]);
+        assert_eq!(status.title(), "Stopped, 0:00 h today");
+
+        app.switch_project("Support");
+        let today = jiff::Zoned::now().date().to_string();
+        assert!(app.is_running());
+        assert_eq!(app.calendar[&today].work_duration.last().unwrap().label.as_deref(), Some("Support"));
+        // already running for that project
+        app.switch_project("Support");
+        assert_eq!(app.calendar[&today].work_duration.len(), 1);
+
+        let status = app.tray_status();
+        assert_eq!(status.project.as_deref(), Some("Support"));
+        assert_eq!(status.projects, vec!["Support".to_owned(), "Work".to_owned()]);
+        let menu = menu(&status);
+        assert!(!menu.find(1).unwrap().enabled);
+        assert!(menu.find(2).unwrap().enabled);
+        assert_eq!(menu.find(100).unwrap().checked, Some(true));
+        assert_eq!(menu.find(101).unwrap().action, Some(TrayAction::SwitchProject("Work".to_owned())));
+        assert!(menu.find(4).unwrap().is_separator());
+
+        app.switch_project("Work");
+        let work_day = &app.calendar[&today];
+        assert_eq!(work_day.work_duration.len(), 2);
+        assert!(work_day.work_duration[0].end.is_some());
+        assert_eq!(work_day.work_duration[1].label.as_deref(), Some("Work"));
+        app.stop_work();
+
+        // an interval without a label, e.g. from older data, is already `Work`
+        app.start_work();
+        app.calendar.get_mut(&today).unwrap().work_duration.last_mut().unwrap().label = None;
+        app.switch_project("Work");
+        assert_eq!(app.calendar[&today].work_duration.len(), 3);
+        app.stop_work();
+    }
+
+    #[test]
+    fn test_focus_mode() {
+        let dir = tempdir().unwrap();
+        let mut app = sample_app(&dir);
+        app.config.focus_minutes = 25;
+        app.config.focus_break_minutes = 5;
+        let today = jiff::Zoned::now().date().to_string();
+
+        app.start_focus();
+        assert!(app.is_running());
+        let session = app.focus.clone().unwrap();
+        assert_eq!(session.phase, FocusPhase::Focus);
+        assert!(session.ends > jiff::Zoned::now().checked_add(Span::new().minutes(24)).unwrap());
+
+        app.end_focus_phase();
+        assert!(!app.is_running());
+        assert_eq!(app.focus.as_ref().unwrap().phase, FocusPhase::Break);
+        assert_eq!(app.calendar[&today].focus_blocks(), 1);
+
+        // after the break tracking waits for the user, the next block is recorded as pause of the first
+        app.end_focus_phase();
+        assert!(!app.is_running());
+        assert_eq!(app.focus.as_ref().unwrap().phase, FocusPhase::Waiting);
+        app.end_focus_phase();
+        assert!(!app.is_running());
+        let _ = app.update(Message::StartFocus);
+        assert!(app.is_running());
+        assert_eq!(app.focus.as_ref().unwrap().phase, FocusPhase::Focus);
+        assert!(app.calendar[&today].work_duration[0].pause.is_some());
+
+        // stopped early, the interval is no focus block
+        app.stop_work();
+        assert!(app.focus.is_none());
+        app.end_focus_phase();
+        let work_day = app.calendar[&today].clone();
+        assert_eq!(work_day.focus_blocks(), 1);
+        assert!(!work_day.work_duration[1].focus);
+
+        let mut synced = std::collections::HashMap::new();
+        crate::gui::sync::apply_changes(&mut synced, diff_calendars(&Default::default(), &app.calendar), app.config.get_workday_span());
+        assert_eq!(synced[&today].focus_blocks(), 1);
+
+        let report = build_range_report(&app.config, &[work_day], None, None);
+        assert_eq!(report.days[0].focus_blocks, 1);
+        assert_eq!(report.totals.focus_blocks, 1);
+        assert!(report.days[0].intervals[0].focus);
+    }
+
+    #[test]
+    fn test_shortcuts_and_palette() {
+        use iced::keyboard::{key::Named, Key, Modifiers};
+        let key = |c: &str| Key::Character(c.into());
+        assert!(matches!(shortcut(Key::Named(Named::Space), Modifiers::empty()), Some(Message::ToggleStartStop)));
+        assert!(matches!(shortcut(Key::Named(Named::ArrowLeft), Modifiers::empty()), Some(Message::PreviousDay)));
+        assert!(matches!(shortcut(key("T"), Modifiers::SHIFT), Some(Message::Today)));
+        assert!(matches!(shortcut(key("v"), Modifiers::empty()), Some(Message::ToggleVacation)));
+        assert!(matches!(shortcut(key("z"), Modifiers::COMMAND | Modifiers::SHIFT), Some(Message::Redo)));
+        assert!(matches!(shortcut(key("k"), Modifiers::COMMAND), Some(Message::OpenPalette)));
+        assert!(shortcut(key("e"), Modifiers::ALT).is_none());
+        assert!(shortcut(key("x"), Modifiers::empty()).is_none());
+
+        let dir = tempdir().unwrap();
+        let mut app = sample_app(&dir);
+        let today = jiff::Zoned::now().date();
+        let _ = app.update(Message::PreviousDay);
+        assert_eq!(app.date, today.yesterday().unwrap());
+        assert!(matches!(app.state, State::NotToday));
+        // nothing starts on another day
+        let _ = app.update(Message::ToggleStartStop);
+        assert!(!app.is_running());
+        let _ = app.update(Message::ToggleVacation);
+        assert!(app.calendar[&app.date.to_string()].vacation);
+        let _ = app.update(Message::ToggleVacation);
+        assert!(!app.calendar[&app.date.to_string()].vacation);
+        let _ = app.update(Message::Today);
+        assert_eq!(app.date, today);
+        let _ = app.update(Message::ToggleStartStop);
+        assert!(app.is_running());
+
+        let names = |query: &str| filter(commands(&app), query).into_iter().map(|command| command.name).collect::<Vec<_>>();
+        assert_eq!(names("stop"), vec!["Stop"]);
+        assert_eq!(names("DAY prev"), vec!["Previous day"]);
+        assert!(names("start").is_empty());
+        assert_eq!(names("").len(), commands(&app).len());
+
+        let _ = app.update(Message::OpenPalette);
+        let _ = app.update(Message::PaletteQueryChanged("stop".to_owned()));
+        let _ = app.update(Message::SubmitPalette);
+        assert!(app.palette.is_none());
+        assert!(!app.is_running());
+        let _ = app.update(Message::RunCommand(Box::new(Message::NextDay)));
+        assert_eq!(app.date, today.tomorrow().unwrap());
+
+        // with the search open only escape does something, it closes the search
+        let _ = app.update(Message::Today);
+        let press = |key: Key| Message::Shortcut(Box::new(shortcut(key, Modifiers::empty()).unwrap()));
+        app.search = Some(SearchForm::default());
+        let _ = app.update(press(Key::Named(Named::Space)));
+        let _ = app.update(press(key("v")));
+        let _ = app.update(press(Key::Named(Named::ArrowLeft)));
+        assert!(!app.is_running());
+        assert!(!app.calendar[&today.to_string()].vacation);
+        assert_eq!(app.date, today);
+        let _ = app.update(press(Key::Named(Named::Escape)));
+        assert!(app.search.is_none());
+        let _ = app.update(press(Key::Named(Named::Space)));
+        assert!(app.is_running());
+        app.stop_work();
+    }
+
+    #[test]
+    fn test_day_navigation() {
+        let dir = tempdir().unwrap();
+        let mut app = sample_app(&dir);
+        let today = jiff::Zoned::now().date();
+        app.start_work();
+        assert!(matches!(app.state, State::Started));
+
+        let _ = app.update(Message::PreviousDay);
+        let _ = app.update(Message::PreviousDay);
+        assert_eq!(app.date, today.checked_sub(jiff::ToSpan::days(2)).unwrap());
+        assert!(matches!(app.state, State::NotToday));
+        // back on today the running interval can be stopped again
+        let _ = app.update(Message::Today);
+        assert!(matches!(app.state, State::Started));
+        app.stop_work();
+        let _ = app.update(Message::NextDay);
+        let _ = app.update(Message::PreviousDay);
+        assert!(matches!(app.state, State::Stopped));
+
+        // left open over midnight, the view moves on to the new day
+        app.date = today.yesterday().unwrap();
+        let _ = app.update(Message::Tick);
+        assert_eq!(app.date, today);
+        let _ = app.update(Message::PreviousDay);
+        let _ = app.update(Message::Tick);
+        assert_eq!(app.date, today.yesterday().unwrap());
+    }
+
+    #[test]
+    fn test_localization() {
+        assert_eq!(Language::parse("de_DE.UTF-8"), Some(Language::German));
+        assert_eq!(Language::parse("en"), Some(Language::English));
+        assert_eq!(Language::parse("C"), None);
+
+        let date = Date::new(2024, 10, 31).unwrap();
+        assert_eq!(Language::English.format_date(date), "2024-10-31");
+        assert_eq!(Language::German.format_date(date), "31.10.2024");
+        assert_eq!(Language::English.format_day(date), "Thu 2024-10-31");
+        assert_eq!(Language::German.format_day(date), "Do 31.10.2024");
+        assert_eq!(Language::English.decimal(8.5, 2), "8.50");
+        assert_eq!(Language::German.decimal(8.5, 2), "8,50");
+        assert_eq!(Language::German.tr("Vacation"), "Urlaub");
+        assert_eq!(Language::German.tr("not in the catalog"), "not in the catalog");
+
+        // headings follow the language, the file is read back in either language
+        let config = Config { language: Language::German, ..sample_config() };
+        let mut work_days = sample_work_days(&config);
+        work_days[0].location = Some(Location::Homeoffice);
+        let csv = serialize_calendar_to_csv(work_days.clone(), &config);
+        assert!(csv.contains("Homeoffice;SUMME ARBEIT;8:30;SUMME PAUSEN;;STUNDENKONTO;"));
+        assert!(csv.contains(";STUNDEN;8,50\n"));
+        assert!(csv.contains("URLAUB;;;;;;\n"));
+        assert!(csv.contains(";BEGINN;ENDE;;DAUER;PAUSE;NOTIZ\n"));
+        let (format, data) = parse_csv(&csv).unwrap();
+        assert_eq!(format, ImportFormat::OwnCsv);
+        assert_eq!(data.intervals.len(), 1);
+        assert_eq!(data.intervals[0].location, Some(Location::Homeoffice));
+        assert_eq!(data.vacation_days, vec![Date::new(2024, 11, 4).unwrap()]);
+
+        let english = serialize_calendar_to_csv(work_days, &sample_config());
+        assert!(english.contains("Home office;SUM WORK;8:30;SUM BREAKS;;CONTINGENT;"));
+        assert!(english.contains(";HOURS;8.50\n"));
+        assert!(english.contains(";START;END;;DURATION;BREAK;NOTE\n"));
+        assert_eq!(parse_location("Büro"), Some(Location::Office));
+        assert_eq!(parse_location("home-office"), Some(Location::Homeoffice));
+    }
+
+    #[test]
+    fn test_appearance() {
+        use iced::keyboard::{Key, Modifiers};
+
+        assert_eq!(ThemeChoice::parse("system"), Some(ThemeChoice::System));
+        assert_eq!(ThemeChoice::parse("tokyonight storm"), Some(ThemeChoice::default()));
+        assert_eq!(ThemeChoice::parse("Gruvbox Dark").unwrap().theme(false), iced::Theme::GruvboxDark);
+        assert_eq!(ThemeChoice::parse("no such theme"), None);
+        assert_eq!(ThemeChoice::System.theme(true), iced::Theme::Dark);
+        assert_eq!(ThemeChoice::System.theme(false), iced::Theme::Light);
+        assert_eq!(ThemeChoice::Named("Removed".to_owned()).theme(false), iced::Theme::TokyoNightStorm);
+        assert_eq!(ThemeChoice::all().len(), iced::Theme::ALL.len() + 1);
+
+        let config = Config { window_size: Some((640., 420.)), ..sample_config() };
+        assert_eq!(initial_size(&sample_config()), iced::Size::new(500., 300.));
+        assert_eq!(initial_size(&config), iced::Size::new(640., 420.));
+        let compact = Config { compact: true, ..config };
+        assert_eq!(initial_size(&compact), COMPACT_WINDOW_SIZE);
+        assert_eq!(full_size(&compact), iced::Size::new(640., 420.));
+
+        // the mini mode's size isn't remembered as the window size
+        let dir = tempdir().unwrap();
+        let mut app = sample_app(&dir);
+        app.config = compact;
+        let _ = app.update(Message::WindowResized(COMPACT_WINDOW_SIZE));
+        let _ = app.update(Message::WindowMoved(iced::Point::new(120., 80.)));
+        assert_eq!(app.config.window_size, Some((640., 420.)));
+        assert_eq!(app.config.window_position, Some((120., 80.)));
+        app.config.compact = false;
+        let _ = app.update(Message::WindowResized(iced::Size::new(700., 500.)));
+        assert_eq!(app.config.window_size, Some((700., 500.)));
+
+        assert!(matches!(shortcut(Key::Character("m".into()), Modifiers::empty()), Some(Message::ToggleCompact)));
+        let themes = filter(commands(&app), "theme nord");
+        assert_eq!(themes.len(), 1);
+        assert!(matches!(&themes[0].message, Message::ThemeSelected(ThemeChoice::Named(name)) if name == "Nord"));
+
+        // saving the appearance keeps the rest of the file as it is on disk
+        let on_disk = "hours_week = 40\n# written by hand\nexport_dir = /home/jane/exports\ntheme = Nord\ncompact = true\nwindow_size = 1x1";
+        let saved = app.config.with_appearance(on_disk);
+        assert_eq!(saved, "hours_week = 40\n# written by hand\nexport_dir = /home/jane/exports\nwindow_size = 700x500\nwindow_position = 120,80");
+    }
+}
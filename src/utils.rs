@@ -1,5 +1,5 @@
 use iced_aw::date_picker::Date;
-use jiff::Span;
+use jiff::{Span, SpanRound, Unit, Zoned};
 use jiff::civil::{date, Weekday};
 use crate::config::Config;
 
@@ -9,6 +9,15 @@ pub fn format_duration(span: &Span) -> String {
     format!("{hours}:{minutes}")
 }
 
+// total length of a span in fractional hours, rounded to whole minutes
+pub fn span_in_hours(span: &Span) -> f32 {
+    let minutes = span
+        .round(SpanRound::new().largest(Unit::Minute))
+        .unwrap()
+        .get_minutes() as f32;
+    minutes / 60.
+}
+
 // returns hours and minutes from a fractional hour value, like 2.7h
 pub fn compute_hours_and_minutes(input_hours: f32) -> (i32, i32) {
     let hours = input_hours.trunc() as i32;
@@ -18,6 +27,32 @@ pub fn compute_hours_and_minutes(input_hours: f32) -> (i32, i32) {
     (hours, minutes)
 }
 
+// formats fractional hours as `7:05`, negative values get a leading `-`
+pub fn format_hours(input_hours: f32) -> String {
+    let (hours, minutes) = compute_hours_and_minutes(input_hours);
+    let sign = if input_hours < 0. { "-" } else { "" };
+    format!("{sign}{}:{:0>2}", hours.abs(), minutes.abs())
+}
+
+// formats the difference to the target hours like `+ 3:20`
+pub fn format_contingent(delta: f32) -> String {
+    let (hours_delta, minutes_delta) = compute_hours_and_minutes(delta);
+    let mut sign = "+";
+    if delta < 0. {
+        sign = "-";
+    }
+    format!("{sign} {}:{:0>2}", hours_delta.abs(), minutes_delta.abs())
+}
+
+pub fn format_time_of_day(zoned: &Zoned) -> String {
+    zoned.time().round(Unit::Minute).unwrap().to_string()[..5].to_owned()
+}
+
+// ISO 8601 with the local offset, like `2024-10-31T08:00:00+01:00`
+pub fn format_iso_timestamp(zoned: &Zoned) -> String {
+    zoned.strftime("%Y-%m-%dT%H:%M:%S%:z").to_string()
+}
+
 pub fn compute_should_hours(from: jiff::civil::Date, to: jiff::civil::Date, config: &Config) -> f32 {
     let hours_week = config.hours_week;
     let work_days = get_num_workdays(from, to);
@@ -26,6 +61,29 @@ pub fn compute_should_hours(from: jiff::civil::Date, to: jiff::civil::Date, conf
    work_days * hours_per_day
 }
 
+// the last day counting towards a month's target, today while the month is still running
+pub fn month_target_end(month: jiff::civil::Date, today: jiff::civil::Date) -> jiff::civil::Date {
+    month.last_of_month().min(today)
+}
+
+// the target of the month containing `month`, also for days without any record. used by every monthly report
+pub fn month_target_hours(month: jiff::civil::Date, today: jiff::civil::Date, config: &Config) -> f32 {
+    let from = month.first_of_month().max(config.start_date);
+    let to = month_target_end(month, today);
+    match from <= to {
+        true => compute_should_hours(from, to, config),
+        false => 0.,
+    }
+}
+
+// the contingent after `end` given everything worked up to then, like the running sum of the csv
+pub fn contingent_until(total_worked: f32, end: jiff::civil::Date, config: &Config) -> f32 {
+    match config.start_date <= end {
+        true => total_worked - compute_should_hours(config.start_date, end, config),
+        false => total_worked,
+    }
+}
+
 pub fn jiff_date_from_picker(picker_date: Date) -> jiff::civil::Date {
     date(picker_date.year as i16, picker_date.month as i8, picker_date.day as i8)
 }
@@ -72,10 +130,10 @@ fn num_workdays_this_week_workdays(workday: Weekday) -> f32 {
 fn sub_for_weekend(from:  &jiff::civil::Date, to:  &jiff::civil::Date) -> f32 {
     let num_days = from.until(*to).unwrap().get_days() + 1;
     let mut num_sub = 0.;
-    let mut tmp_date = from.clone();
+    let mut tmp_date = *from;
     for _i in 0.. num_days {
         if tmp_date.weekday() == Weekday::Saturday || tmp_date.weekday() == Weekday::Sunday {
-            num_sub = num_sub + 1.;
+            num_sub += 1.;
         }
         tmp_date = tmp_date.tomorrow().unwrap();
     }